@@ -1,21 +1,33 @@
 use std::fs::{read_dir, File};
+use std::path::PathBuf;
 use chrono::NaiveDate;
 use derive_builder::Builder;
 #[cfg(test)]
 use mockall::automock;
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
+use std::str::FromStr;
 use serde_json::from_reader;
 use crate::period::{Period, PeriodConfigurationVaultValue, PeriodsConfiguration};
-use crate::amounts::Amount;
+use crate::amounts::{Add, Amount, CurrencyIdent};
 use crate::amounts::exchange_rates::ExchangeRates;
+use crate::error::{ChoError, Context};
 use crate::remaining_operation::core_types::{GroupBuilder, Operand, OperandBuilder};
 use crate::remaining_operation::core_types::group::Group;
 use crate::remaining_operation::operand_builders_helpers::timeline::{TimelineOperandBuilderHelper, TimelineOperandEnd};
 use crate::vault::Vault;
+use loan::PrincipalPayment;
+use savings::SavingsConfiguration;
+use transactions::Transaction;
+
+pub mod checking;
+pub mod loan;
+pub mod recording;
+pub mod savings;
+pub mod transactions;
 
 // Public traits
-pub type Figure = u32;
+pub type Figure = Decimal;
 const ACCOUNT_DIR: &str = "accounts";
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -26,14 +38,14 @@ pub struct FoundAmount {
 
 impl FoundAmount {
     // TODO Rename to "into amount" once the entire codebase has adopted the amount module
-    fn into_remaining_module_amount(self, currency: &String, exchange_rates: &ExchangeRates) -> Result<Amount, String> {
-        exchange_rates.new_amount(currency, Decimal::from(self.figure))
+    fn into_remaining_module_amount(self, currency: &String, exchange_rates: &ExchangeRates, date: &NaiveDate) -> Result<Amount, ChoError> {
+        exchange_rates.new_amount_at(currency, self.figure, date)
     }
 }
 
 #[cfg_attr(test, automock)]
 pub trait QueriableAccount {
-    fn amount_at(&self, date: &NaiveDate) -> Result<FoundAmount, String>;
+    fn amount_at(&self, date: &NaiveDate) -> Result<FoundAmount, ChoError>;
     fn name(&self) -> &String;
     fn currency(&self) -> &String;
 }
@@ -44,22 +56,34 @@ pub struct AccountGetter{
 }
 
 impl AccountGetter {
+    pub fn from_vault<V: Vault>(vault: &V) -> Result<AccountGetter, ChoError>{
+        Ok(AccountGetter{
+            accounts: Self::read_account_files(vault)?.into_iter().map(|(_, account)| account).collect()
+        })
+    }
+
+    /// The account with that name, along with the file it is stored in
+    pub fn find_account_file<V: Vault>(vault: &V, name: &str) -> Result<(PathBuf, AccountJson), ChoError> {
+        Self::read_account_files(vault)?
+            .into_iter()
+            .find(|(_, account)| account.name == name)
+            .ok_or(format!("No account is named \"{}\"", name).into())
+    }
+
     // TODO - This should read the account from the Vault, otherwise this is breaking the abstraction of
     //        however we choose to store "state". We assume it is always through a file
-    pub fn from_vault<V: Vault>(vault: &V) -> Result<AccountGetter, String>{
-        let directory = vault.path();
-        let dir_reader = match read_dir(directory.join(ACCOUNT_DIR)) {
-            Err(why) => {
-                return Err("Could not read the Accounts directory: ".to_string() + &why.to_string())
-            }
-            Ok(reader) => reader,
-        };
+    fn read_account_files<V: Vault>(vault: &V) -> Result<Vec<(PathBuf, AccountJson)>, ChoError> {
+        let directory = vault.path().join(ACCOUNT_DIR);
+        let dir_reader = read_dir(&directory)
+            .map_err(|source| ChoError::File { path: directory.clone(), source })
+            .context(|| "Could not read the Accounts directory")?;
 
-        let mut accounts: Vec<AccountJson> = Vec::new();
+        let mut accounts: Vec<(PathBuf, AccountJson)> = Vec::new();
 
         for maybe_dir_entry in dir_reader {
-            let dir_entry =
-                maybe_dir_entry.map_err(|why| return format!("Could not read file: {}", why))?;
+            let dir_entry = maybe_dir_entry
+                .map_err(|source| ChoError::File { path: directory.clone(), source })
+                .context(|| "Could not read the Accounts directory")?;
 
             let path = dir_entry.path();
             let path_str = if let Some(s) = &(path).to_str() {
@@ -68,38 +92,53 @@ impl AccountGetter {
                 "(unable to get filename)"
             };
 
-            let file_type = dir_entry.file_type().map_err(|why| {
-                return format!("Could not read the file type of {}: {}", path_str, why);
-            })?;
+            let file_type = dir_entry
+                .file_type()
+                .map_err(|source| ChoError::File { path: path.clone(), source })
+                .context(|| "Could not read the file type")?;
 
             if file_type.is_file() {
-                let file = match File::open(&path) {
-                    Err(why) => return Err(format!("Could not read file {}: {}", path_str, why)),
-                    Ok(file) => file,
-                };
-
-                let account: AccountJson = match from_reader(file) {
-                    Err(why) => {
-                        return Err(format!(
-                            "Could not parse account for file {}: {}",
-                            path_str, why
-                        ))
-                    }
-                    Ok(file) => file,
-                };
+                let file = File::open(&path)
+                    .map_err(|source| ChoError::File { path: path.clone(), source })
+                    .context(|| "Could not read account file")?;
+                let account: AccountJson = from_reader(file)
+                    .context(|| format!("Could not parse account for file {}", path_str))?;
+
+                if !account.amortisation.is_empty() && account.kind != AccountKind::Loan {
+                    return Err(format!(
+                        "Account \"{}\" in file {}: only loans can declare an amortisation schedule",
+                        account.name, path_str
+                    ).into());
+                }
 
-                accounts.push(account);
+                accounts.push((path, account));
             }
         }
 
-        Ok(AccountGetter{
-            accounts: accounts
+        Ok(accounts)
+    }
+
+    /// The sum of the balances of every account on the date
+    pub fn total_at(&self, date: &NaiveDate, exchange_rates: &ExchangeRates, target_currency: &CurrencyIdent) -> Result<Amount, ChoError> {
+        self.accounts.iter().try_fold(exchange_rates.zero(target_currency)?, |total, account| {
+            let amount = account
+                .amortised_figure_at(date)
+                .and_then(|figure| account.balance(figure, date, exchange_rates))
+                .context(|| format!("Account \"{}\"", account.name))?;
+            Ok(total.add(&amount))
         })
     }
 }
 
+#[cfg(test)]
+impl AccountGetter {
+    pub(crate) fn new(accounts: Vec<AccountJson>) -> AccountGetter {
+        AccountGetter { accounts }
+    }
+}
+
 impl GroupBuilder<AccountJson> for AccountGetter {
-    fn build(self) -> Result<(String, Vec<AccountJson>), String> {
+    fn build(self) -> Result<(String, Vec<AccountJson>), ChoError> {
         Ok(("Accounts".into(), self.accounts))
     }
 }
@@ -108,13 +147,16 @@ impl GroupBuilder<AccountJson> for AccountGetter {
 #[cfg(test)]
 mod tests_get_accounts {
     use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
     use std::collections::HashSet;
     use std::fs::{create_dir, File};
     use std::io::prelude::*;
     use std::path::{Path, PathBuf};
     use tempfile::{tempdir, TempDir};
 
-    use crate::accounts::{AccountGetter, AccountJson, AmountListItem, ACCOUNT_DIR};
+    use crate::amounts::exchange_rates::ExchangeRates;
+    use crate::accounts::{AccountGetter, AccountJson, AccountKind, AmountListItem, BalanceProjection, ACCOUNT_DIR};
+    use crate::error::ChoError;
     use crate::vault::Vault;
 
     struct MockVault {
@@ -129,9 +171,21 @@ mod tests_get_accounts {
         fn read_vault_values<T: serde::de::DeserializeOwned>(
             &self,
             _name: String,
-        ) -> Result<T, String> {
+        ) -> Result<T, ChoError> {
             todo!()
         }
+
+        fn read_optional_vault_values<T: serde::de::DeserializeOwned>(
+            &self,
+            _name: String,
+        ) -> Result<Option<T>, ChoError> {
+            Ok(None)
+        }
+
+        /// The accounts are files of their own, the configuration is never written
+        fn write_vault_values<T: serde::Serialize>(&self, _name: String, _value: &T) -> Result<(), ChoError> {
+            Ok(())
+        }
     }
 
     fn create_account_file(directory: &TempDir, name: &str, content: &str) {
@@ -175,17 +229,22 @@ mod tests_get_accounts {
                 amounts: vec![
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 04, 08).unwrap(),
-                        amount: 55000,
+                        amount: dec!(55000),
                     },
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 04, 10).unwrap(),
-                        amount: 53000,
+                        amount: dec!(53000),
                     },
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 04, 12).unwrap(),
-                        amount: 60000,
+                        amount: dec!(60000),
                     },
                 ],
+                savings: None,
+                projection: BalanceProjection::Flat,
+                kind: AccountKind::Asset,
+                amortisation: vec![],
+                transactions: vec![],
             },
             AccountJson {
                 name: "account_right".to_string(),
@@ -193,17 +252,22 @@ mod tests_get_accounts {
                 amounts: vec![
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 02, 03).unwrap(),
-                        amount: 5000,
+                        amount: dec!(5000),
                     },
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 03, 13).unwrap(),
-                        amount: 5200,
+                        amount: dec!(5200),
                     },
                     AmountListItem {
                         date: NaiveDate::from_ymd_opt(2023, 05, 16).unwrap(),
-                        amount: 6000,
+                        amount: dec!(6000),
                     },
                 ],
+                savings: None,
+                projection: BalanceProjection::Flat,
+                kind: AccountKind::Asset,
+                amortisation: vec![],
+                transactions: vec![],
             },
         ]);
 
@@ -216,6 +280,48 @@ mod tests_get_accounts {
             expected_accounts
         )
     }
+
+    #[test]
+    fn parse_accounts__amortisation_of_an_asset() {
+        let directory = tempdir().unwrap();
+        create_dir(Path::join(directory.path(), ACCOUNT_DIR)).unwrap();
+        create_account_file(&directory, "current.json", r#"{
+"name": "current",
+"currency": "EUR",
+"amounts": [{"date": "2023-02-03", "amount": 5000}],
+"amortisation": [{"date": "2023-03-01", "principal": "100"}]
+}"#);
+
+        let vault = MockVault {
+            path: directory.path().to_path_buf(),
+        };
+
+        let error = AccountGetter::from_vault(&vault).err().unwrap();
+        assert!(error.to_string().ends_with("only loans can declare an amortisation schedule"), "{}", error);
+    }
+
+    #[test]
+    fn parse_accounts__errors_keep_their_exit_code() {
+        let directory = tempdir().unwrap();
+        let vault = MockVault {
+            path: directory.path().to_path_buf(),
+        };
+        assert_eq!(AccountGetter::from_vault(&vault).err().unwrap().exit_code(), 3);
+
+        create_dir(Path::join(directory.path(), ACCOUNT_DIR)).unwrap();
+        create_account_file(&directory, "unknown.json", r#"{
+"name": "unknown",
+"currency": "XXQ",
+"amounts": [{"date": "2023-02-03", "amount": 5000}]
+}"#);
+        let accounts = AccountGetter::from_vault(&vault).unwrap();
+        let error = accounts.total_at(&NaiveDate::from_ymd_opt(2023, 2, 3).unwrap(), &ExchangeRates::for_tests(), &"EUR".to_string())
+            .err().unwrap();
+        assert_eq!(error.exit_code(), 5, "{}", error);
+
+        create_account_file(&directory, "broken.json", "{");
+        assert_eq!(AccountGetter::from_vault(&vault).err().unwrap().exit_code(), 4);
+    }
 }
 
 // JSON implementation
@@ -224,6 +330,52 @@ pub struct AccountJson {
     name: String,
     currency: String,
     amounts: Vec<AmountListItem>,
+    /// Only set for savings accounts, which earn interest
+    #[serde(default)]
+    savings: Option<SavingsConfiguration>,
+    #[serde(default)]
+    projection: BalanceProjection,
+    #[serde(default)]
+    kind: AccountKind,
+    /// The principal repaid on each date, only for loans
+    #[serde(default)]
+    amortisation: Vec<PrincipalPayment>,
+    /// Movements of the account, used to compute the balance between recorded amounts
+    #[serde(default)]
+    transactions: Vec<Transaction>,
+}
+
+/// Liabilities record what is owed as a positive amount
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum AccountKind {
+    #[default]
+    #[serde(rename = "asset")]
+    Asset,
+    #[serde(rename = "credit_card")]
+    CreditCard,
+    #[serde(rename = "loan")]
+    Loan,
+}
+
+impl AccountKind {
+    fn is_liability(&self) -> bool {
+        match self {
+            AccountKind::Asset => false,
+            AccountKind::CreditCard | AccountKind::Loan => true,
+        }
+    }
+}
+
+/// How the balance at the end of the period is predicted when no amount was recorded for that day
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum BalanceProjection {
+    /// The balance stays at the last recorded amount
+    #[default]
+    #[serde(rename = "flat")]
+    Flat,
+    /// The balance keeps changing at the pace it changed since the start of the period
+    #[serde(rename = "trend")]
+    Trend,
 }
 
 #[cfg(test)]
@@ -232,22 +384,87 @@ impl AccountJson {
         AccountJson{
             name,
             currency,
-            amounts: amounts.into_iter().map(|(date, amount)| AmountListItem{date, amount}).collect()
+            amounts: amounts.into_iter().map(|(date, amount)| AmountListItem{date, amount}).collect(),
+            savings: None,
+            projection: BalanceProjection::Flat,
+            kind: AccountKind::Asset,
+            amortisation: vec![],
+            transactions: vec![],
+        }
+    }
+}
+
+impl AccountJson {
+    /// The recorded figure as a balance, liabilities count negatively
+    fn balance(&self, figure: Figure, date: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Amount, ChoError> {
+        exchange_rates.new_amount_at(&self.currency, self.signed_figure(figure), date)
+    }
+
+    /// Turns a recorded figure into a balance and back: liabilities are recorded as what is owed
+    pub fn signed_figure(&self, figure: Figure) -> Figure {
+        if self.kind.is_liability() { -figure } else { figure }
+    }
+
+    /// The amount on the date, minus the principal scheduled to be repaid since the balance was last known
+    fn amortised_figure_at(&self, date: &NaiveDate) -> Result<Figure, ChoError> {
+        let found_amount = self.amount_at(date)?;
+        let Some(last_known_date) = self.last_known_date(date) else {
+            return Ok(found_amount.figure);
+        };
+        let scheduled_principal: Decimal = self
+            .amortisation
+            .iter()
+            .filter(|payment| payment.date > last_known_date && payment.date <= *date)
+            .map(|payment| payment.principal)
+            .sum();
+        Ok(found_amount.figure - scheduled_principal)
+    }
+
+    /// The balance expected at the end of the period, from the last one known before it.
+    /// Loans with an amortisation schedule follow it instead of their projection. The figure is not rounded, the
+    /// amount made from it is, to the minor units of the account's currency.
+    fn predicted_end_figure(&self, period: &Period, start_figure: Figure, last_known_date: &NaiveDate) -> Result<Figure, ChoError> {
+        if !self.amortisation.is_empty() {
+            return self.amortised_figure_at(&period.end_date);
         }
+
+        let last_figure = self.amount_at(last_known_date)?.figure;
+        let elapsed_days = (*last_known_date - period.start_date).num_days();
+        Ok(match self.projection {
+            BalanceProjection::Trend if elapsed_days > 0 => {
+                let remaining_days = (period.end_date - *last_known_date).num_days();
+                let change_per_day = (last_figure - start_figure) / Decimal::from(elapsed_days);
+                last_figure + change_per_day * Decimal::from(remaining_days)
+            }
+            _ => last_figure,
+        })
     }
 }
 
-// TODO - Unit tests for this
 impl OperandBuilder for AccountJson {
-    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, String> {
+    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
         let current_period = period_config.period_for_date(today)?;
-        let start_amount = self.amount_at(&current_period.start_date)?.into_remaining_module_amount(self.currency(), exchange_rates)?;
-        let end_amount = self.amount_at(&current_period.end_date)?.into_remaining_module_amount(self.currency(), exchange_rates)?;
+        let start_figure = self.amortised_figure_at(&current_period.start_date)?;
+        let start_amount = self.balance(start_figure, &current_period.start_date, exchange_rates)?;
+
+        let recorded_on_end = self.recorded_amount(&current_period.end_date).is_some();
+        let wrapper_end_amount = match self.last_known_date(&current_period.end_date) {
+            Some(last_known_date) if !recorded_on_end => TimelineOperandEnd::Predicted(
+                self.balance(
+                    self.predicted_end_figure(&current_period, start_figure, &last_known_date)?,
+                    &current_period.end_date,
+                    exchange_rates,
+                )?,
+            ),
+            _ => TimelineOperandEnd::Current(
+                self.balance(self.amount_at(&current_period.end_date)?.figure, &current_period.end_date, exchange_rates)?,
+            ),
+        };
 
         let builder = TimelineOperandBuilderHelper {
             name: self.name.clone(),
             start_amount,
-            wrapper_end_amount: TimelineOperandEnd::Current(end_amount)
+            wrapper_end_amount,
         };
         builder.build()
     }
@@ -256,9 +473,35 @@ impl OperandBuilder for AccountJson {
 #[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone)]
 pub struct AmountListItem {
     date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_figure")]
     amount: Figure,
 }
 
+/// Amounts can be written as numbers or as strings, the latter keep every decimal exactly
+fn deserialize_figure<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Figure, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawFigure {
+        Number(serde_json::Number),
+        String(String),
+    }
+
+    let raw_figure = match RawFigure::deserialize(deserializer)? {
+        RawFigure::Number(number) => number.to_string(),
+        RawFigure::String(string) => string,
+    };
+    Decimal::from_str(&raw_figure)
+        .or_else(|_| Decimal::from_scientific(&raw_figure))
+        .map_err(|error| de::Error::custom(format!("Invalid amount {}: {}", raw_figure, error)))
+}
+
+impl AccountJson {
+    /// The amount recorded for that exact date
+    pub fn recorded_amount(&self, date: &NaiveDate) -> Option<Figure> {
+        self.amounts.iter().find(|item| item.date == *date).map(|item| item.amount)
+    }
+}
+
 impl QueriableAccount for AccountJson {
     /// This function takes a date and returns the amount that was
     /// available on the account on that date.
@@ -266,26 +509,27 @@ impl QueriableAccount for AccountJson {
     /// The function searches through the account's amount history. If
     /// an amount was recorded for the date, it returns that amount.
     /// Otherwise, it returns the last recorded amount before that
-    /// date.
+    /// date, plus the transactions made since.
     ///
-    /// If no amount was recorded for the passed date, the
+    /// If no amount was recorded for the passed date and the
+    /// transactions do not cover the days since the last one, the
     /// FoundAmount's `estimated` field is set to true.
-    fn amount_at(&self, date: &NaiveDate) -> Result<FoundAmount, String> {
+    fn amount_at(&self, date: &NaiveDate) -> Result<FoundAmount, ChoError> {
         let mut iter = self.amounts.iter().peekable();
 
         let Some(mut item_left) = &iter.next() else {
-            return Err("The account has no amount history".to_string());
+            return Err("The account has no amount history".into());
         };
 
         if *date < item_left.date {
-            return Err("The requested date is before the start of the amount history".to_string());
+            return Err("The requested date is before the start of the amount history".into());
         }
 
         loop {
             let date_between_left_and_right = match &iter.peek() {
                 Some(item_right) => {
                     if item_left.date > item_right.date {
-                        return Err("Amount history out of order".to_string());
+                        return Err("Amount history out of order".into());
                     }
 
                     *date > item_left.date && *date < item_right.date
@@ -301,6 +545,12 @@ impl QueriableAccount for AccountJson {
             }
 
             if date_between_left_and_right {
+                if self.transactions_cover(&item_left.date) {
+                    return Ok(FoundAmount {
+                        figure: item_left.amount + self.transactions_between(&item_left.date, date),
+                        estimated: false,
+                    });
+                }
                 return Ok(FoundAmount {
                     figure: item_left.amount,
                     estimated: true,
@@ -309,7 +559,7 @@ impl QueriableAccount for AccountJson {
 
             item_left = match &iter.next() {
                 Some(v) => v,
-                None => return Err("Reached end of the list but did not return amount".to_string()),
+                None => return Err("Reached end of the list but did not return amount".into()),
             }
         }
     }
@@ -326,8 +576,9 @@ impl QueriableAccount for AccountJson {
 #[cfg(test)]
 mod tests_accountjson_amount_at {
     use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
 
-    use super::{AccountJson, AmountListItem, Figure, FoundAmount, QueriableAccount};
+    use super::{AccountJson, AccountKind, AmountListItem, BalanceProjection, Figure, FoundAmount, QueriableAccount};
 
     fn date(day: u32) -> NaiveDate {
         return NaiveDate::from_ymd_opt(1995, 5, day).unwrap();
@@ -337,11 +588,11 @@ mod tests_accountjson_amount_at {
         return Vec::from([
             AmountListItem {
                 date: date(15),
-                amount: 1500,
+                amount: dec!(1500),
             },
             AmountListItem {
                 date: date(19),
-                amount: 1800,
+                amount: dec!(1800),
             },
         ]);
     }
@@ -350,11 +601,11 @@ mod tests_accountjson_amount_at {
         return Vec::from([
             AmountListItem {
                 date: date(19),
-                amount: 1800,
+                amount: dec!(1800),
             },
             AmountListItem {
                 date: date(15),
-                amount: 1500,
+                amount: dec!(1500),
             },
         ]);
     }
@@ -364,6 +615,11 @@ mod tests_accountjson_amount_at {
             name: "Test account".to_string(),
             currency: String::from("EN"),
             amounts: list,
+            savings: None,
+            projection: BalanceProjection::Flat,
+            kind: AccountKind::Asset,
+            amortisation: vec![],
+            transactions: vec![],
         };
     }
 
@@ -381,34 +637,34 @@ mod tests_accountjson_amount_at {
     fn before_start() {
         assert_eq!(
             sample_account(list_in_order()).amount_at(&date(13)),
-            Result::Err("The requested date is before the start of the amount history".to_string())
+            Result::Err("The requested date is before the start of the amount history".into())
         )
     }
 
     #[test]
     fn first_date() {
-        assert_correct(15, 1500, false)
+        assert_correct(15, dec!(1500), false)
     }
 
     #[test]
     fn between_date() {
-        assert_correct(17, 1500, true)
+        assert_correct(17, dec!(1500), true)
     }
 
     #[test]
     fn second_date() {
-        assert_correct(19, 1800, false)
+        assert_correct(19, dec!(1800), false)
     }
 
     #[test]
     fn after_last_date() {
-        assert_correct(22, 1800, true)
+        assert_correct(22, dec!(1800), true)
     }
 
     fn assert_out_of_order(day: u32) {
         assert_eq!(
             sample_account(list_out_of_order()).amount_at(&date(day)),
-            Result::Err("Amount history out of order".to_string())
+            Result::Err("Amount history out of order".into())
         )
     }
 
@@ -421,4 +677,120 @@ mod tests_accountjson_amount_at {
     fn error_list_out_of_order_later_date() {
         assert_out_of_order(22);
     }
+
+    #[test]
+    fn amounts_as_numbers_and_strings() {
+        let amounts: Vec<AmountListItem> = serde_json::from_value(serde_json::json!([
+            {"date": "1995-05-15", "amount": 1500},
+            {"date": "1995-05-16", "amount": -20.5},
+            {"date": "1995-05-17", "amount": "-1234.56"},
+        ])).unwrap();
+        assert_eq!(
+            amounts.into_iter().map(|item| item.amount).collect::<Vec<Figure>>(),
+            vec![dec!(1500), dec!(-20.5), dec!(-1234.56)]
+        );
+
+        let invalid = serde_json::from_value::<AmountListItem>(serde_json::json!({"date": "1995-05-15", "amount": "12,50"}));
+        assert!(invalid.is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_accountjson_build {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use crate::remaining_operation::core_types::IllustrationValue;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    fn build(account: AccountJson, today: NaiveDate) -> Vec<(String, IllustrationValue)> {
+        OperandBuilder::build(
+            account,
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &today,
+            &ExchangeRates::for_tests(),
+        )
+            .unwrap()
+            .unwrap()
+            .illustration
+    }
+
+    fn end_amount_and_committed(illustration: Vec<(String, IllustrationValue)>) -> (IllustrationValue, IllustrationValue) {
+        (illustration[1].1.clone(), illustration[2].1.clone())
+    }
+
+    #[test]
+    fn recorded_on_period_end() {
+        let account = AccountJson::new("Current".to_string(), "EUR".to_string(), vec![(date(1), dec!(1000)), (date(30), dec!(400))]);
+        assert_eq!(
+            end_amount_and_committed(build(account, date(10))),
+            (IllustrationValue::Amount(ExchangeRates::for_tests().euro("400")), IllustrationValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn flat_projection() {
+        let account = AccountJson::new("Current".to_string(), "EUR".to_string(), vec![(date(1), dec!(1000)), (date(10), dec!(700))]);
+        assert_eq!(
+            end_amount_and_committed(build(account, date(12))),
+            (IllustrationValue::Amount(ExchangeRates::for_tests().euro("700")), IllustrationValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn credit_card() {
+        let mut account = AccountJson::new("Card".to_string(), "EUR".to_string(), vec![(date(1), dec!(300)), (date(30), dec!(450.5))]);
+        account.kind = AccountKind::CreditCard;
+        let ex = ExchangeRates::for_tests();
+        assert_eq!(build(account, date(10)), vec![
+            ("Period start amount".to_string(), IllustrationValue::Amount(ex.euro("-300"))),
+            ("Period end amount".to_string(), IllustrationValue::Amount(ex.euro("-450.5"))),
+            ("Committed".to_string(), IllustrationValue::Bool(true)),
+            ("Difference".to_string(), IllustrationValue::Amount(ex.euro("-150.5"))),
+        ]);
+    }
+
+    #[test]
+    fn trend_projection() {
+        let mut account = AccountJson::new("Current".to_string(), "EUR".to_string(), vec![(date(1), dec!(1000)), (date(10), dec!(730))]);
+        account.projection = BalanceProjection::Trend;
+        // 30 spent per day, 20 days remain after the last amount
+        assert_eq!(
+            end_amount_and_committed(build(account.clone(), date(12))),
+            (IllustrationValue::Amount(ExchangeRates::for_tests().euro("130")), IllustrationValue::Bool(false))
+        );
+
+        // Nothing was recorded during the period yet
+        account.amounts.truncate(1);
+        assert_eq!(
+            end_amount_and_committed(build(account, date(12))),
+            (IllustrationValue::Amount(ExchangeRates::for_tests().euro("1000")), IllustrationValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn trend_projection_follows_minor_units() {
+        let ex = ExchangeRates::from_indent_and_rates(
+            vec![("KWD".to_string(), dec!(3))],
+            &crate::amounts::currency_table::CurrencyTable::iso_4217(),
+        ).expect("Can create exchange rates");
+        let mut account = AccountJson::new("Current".to_string(), "KWD".to_string(), vec![(date(1), dec!(1000)), (date(4), dec!(900))]);
+        account.projection = BalanceProjection::Trend;
+        let illustration = OperandBuilder::build(
+            account,
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &date(5),
+            &ex,
+        ).expect("Can build operand").expect("Account has an operand").illustration;
+
+        // 100/3 spent per day, 26 days remain after the last amount. The dinar has three decimals.
+        assert_eq!(
+            end_amount_and_committed(illustration).0,
+            IllustrationValue::Amount(ex.new_amount(&"KWD".to_string(), dec!(33.333)).unwrap())
+        );
+    }
 }
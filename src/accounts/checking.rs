@@ -0,0 +1,130 @@
+use super::{AccountJson, AccountKind, ACCOUNT_DIR};
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::check::{vault_file_name, Problem};
+use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::vault::Vault;
+use chrono::NaiveDate;
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+
+/// Every problem of the account files. Dates are only checked against the periods when the periods
+/// configuration could be read, currencies when the exchange rates could.
+pub fn check_accounts<V: Vault>(
+    vault: &V,
+    period_config: Option<&PeriodConfigurationVaultValue>,
+    exchange_rates: Option<&ExchangeRates>,
+) -> Vec<Problem> {
+    let mut paths = match read_dir(vault.path().join(ACCOUNT_DIR)) {
+        Ok(dir_reader) => dir_reader
+            .filter_map(|maybe_dir_entry| maybe_dir_entry.ok().map(|dir_entry| dir_entry.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<PathBuf>>(),
+        Err(why) => {
+            return vec![Problem::new(ACCOUNT_DIR, format!("Could not read the Accounts directory: {}", why))]
+        }
+    };
+    paths.sort();
+
+    let mut problems = vec![];
+    let mut names: Vec<(String, String)> = vec![];
+    for path in paths {
+        let file = vault_file_name(vault, &path);
+        let account = read_to_string(&path)
+            .map_err(|why| format!("Could not read the file: {}", why))
+            .and_then(|content| serde_json::from_str::<AccountJson>(&content).map_err(|why| why.to_string()));
+        let account = match account {
+            Ok(account) => account,
+            Err(error) => {
+                problems.push(Problem::new(&file, error));
+                continue;
+            }
+        };
+
+        if let Some((_, other_file)) = names.iter().find(|(name, _)| *name == account.name) {
+            problems.push(Problem::new(&file, format!("Account \"{}\" is also defined in {}", account.name, other_file)).at_key("name"));
+        }
+        names.push((account.name.clone(), file.clone()));
+        problems.extend(check_account(&file, &account, period_config, exchange_rates));
+    }
+    problems
+}
+
+fn check_account(
+    file: &str,
+    account: &AccountJson,
+    period_config: Option<&PeriodConfigurationVaultValue>,
+    exchange_rates: Option<&ExchangeRates>,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    if let Some(error) = exchange_rates.and_then(|ex| ex.zero(&account.currency).err()) {
+        problems.push(Problem::new(file, error).at_key("currency"));
+    }
+    if !account.amortisation.is_empty() && account.kind != AccountKind::Loan {
+        problems.push(Problem::new(file, "Only loans can declare an amortisation schedule".to_string()).at_key("amortisation"));
+    }
+    if account.amounts.is_empty() {
+        problems.push(Problem::new(file, "The account has no amount history".to_string()).at_key("amounts"));
+    }
+
+    let dated_lists: [(&str, Vec<NaiveDate>); 3] = [
+        ("amounts", account.amounts.iter().map(|item| item.date).collect()),
+        ("transactions", account.transactions.iter().map(|transaction| transaction.date).collect()),
+        ("amortisation", account.amortisation.iter().map(|payment| payment.date).collect()),
+    ];
+    for (key, dates) in dated_lists {
+        for (index, date) in dates.iter().enumerate() {
+            if key == "amounts" && index > 0 && dates[index - 1] >= *date {
+                let error = if dates[index - 1] == *date { "Amount recorded twice on" } else { "Amount history out of order on" };
+                problems.push(Problem::new(file, format!("{} {}", error, date)).at_key(key).at_index(index));
+            }
+            if let Some(error) = period_config.and_then(|period_config| period_config.period_for_date(date).err()) {
+                problems.push(Problem::new(file, format!("{}: {}", date, error)).at_key(key).at_index(index));
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultImpl;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::fs::{create_dir, write};
+
+    #[test]
+    fn every_problem_is_reported() {
+        let (directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+        let accounts = directory.path().join(ACCOUNT_DIR);
+        create_dir(&accounts).unwrap();
+        write(accounts.join("a_current.json"), r#"{"name": "Current", "currency": "GBP", "amounts": [
+            {"date": "2025-09-01", "amount": 1000},
+            {"date": "2025-09-05", "amount": 800},
+            {"date": "2025-09-03", "amount": 900},
+            {"date": "2025-09-03", "amount": 950}
+        ], "amortisation": [{"date": "2025-09-30", "principal": "10"}]}"#).unwrap();
+        write(accounts.join("b_current.json"), r#"{"name": "Current", "currency": "EUR", "amounts": [],
+            "transactions": [{"date": "2024-12-31", "amount": -10}]}"#).unwrap();
+        write(accounts.join("c_broken.json"), r#"{"name": "Broken", "currency": "EUR"}"#).unwrap();
+
+        let period_config: PeriodConfigurationVaultValue = serde_json::from_value(json!({
+            "type": "fixed_length", "start_date": "2025-01-01", "period_in_days": 14
+        })).unwrap();
+        let problems: Vec<String> = check_accounts(&vault, Some(&period_config), Some(&ExchangeRates::for_tests()))
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect();
+        assert_eq!(problems, vec![
+            "accounts/a_current.json: currency: Could not find currency ident: GBP",
+            "accounts/a_current.json: amortisation: Only loans can declare an amortisation schedule",
+            "accounts/a_current.json: amounts[2]: Amount history out of order on 2025-09-03",
+            "accounts/a_current.json: amounts[3]: Amount recorded twice on 2025-09-03",
+            "accounts/b_current.json: name: Account \"Current\" is also defined in accounts/a_current.json",
+            "accounts/b_current.json: amounts: The account has no amount history",
+            "accounts/b_current.json: transactions[0]: 2024-12-31: Date is before PeriodsConfiguration's start",
+            "accounts/c_broken.json: missing field `amounts` at line 1 column 37",
+        ]);
+    }
+}
@@ -0,0 +1,146 @@
+use super::{AccountGetter, AccountJson, AccountKind};
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::error::ChoError;
+use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::remaining_operation::core_types::{GroupBuilder, IllustrationValue, Operand, OperandBuilder};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Part of a loan repaid on a date. Interest and fees are not part of it.
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone)]
+pub struct PrincipalPayment {
+    pub(super) date: NaiveDate,
+    pub(super) principal: Decimal,
+}
+
+/// The principal of a loan repaid during the current period
+pub struct LoanRepayment {
+    account: AccountJson,
+}
+
+impl OperandBuilder for LoanRepayment {
+    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
+        let current_period = period_config.period_for_date(today)?;
+        let payments: Vec<&PrincipalPayment> = self
+            .account
+            .amortisation
+            .iter()
+            .filter(|payment| current_period.contains(&payment.date))
+            .collect();
+        let Some(last_payment) = payments.last() else {
+            return Ok(None);
+        };
+
+        // The accounts already show the payments made until today
+        let upcoming: Vec<&&PrincipalPayment> = payments.iter().filter(|payment| payment.date > *today).collect();
+        let principal: Decimal = upcoming.iter().map(|payment| payment.principal).sum();
+
+        Ok(Some(Operand {
+            name: self.account.name.clone(),
+            amount: exchange_rates.new_amount(&self.account.currency, -principal)?,
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(upcoming.is_empty())),
+                ("Date".to_string(), IllustrationValue::Date(upcoming.first().unwrap_or(&last_payment).date)),
+            ],
+        }))
+    }
+}
+
+/// The loans of the vault that declare an amortisation schedule
+pub struct LoanRepaymentsGetter {
+    loans: Vec<AccountJson>,
+}
+
+impl LoanRepaymentsGetter {
+    pub fn from_accounts(accounts: &AccountGetter) -> LoanRepaymentsGetter {
+        LoanRepaymentsGetter {
+            loans: accounts
+                .accounts
+                .iter()
+                .filter(|account| account.kind == AccountKind::Loan && !account.amortisation.is_empty())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loans.is_empty()
+    }
+}
+
+impl GroupBuilder<LoanRepayment> for LoanRepaymentsGetter {
+    fn build(self) -> Result<(String, Vec<LoanRepayment>), ChoError> {
+        Ok((
+            "Loan repayments".into(),
+            self.loans.into_iter().map(|account| LoanRepayment { account }).collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    fn loan() -> AccountJson {
+        serde_json::from_value(json!({
+            "name": "Car loan",
+            "currency": "EUR",
+            "kind": "loan",
+            "amounts": [{"date": "2025-08-01", "amount": 10000}, {"date": "2025-09-05", "amount": 9600}],
+            "amortisation": [
+                {"date": "2025-09-05", "principal": "400"},
+                {"date": "2025-10-05", "principal": "410"}
+            ]
+        })).expect("Can parse loan")
+    }
+
+    fn build<O: OperandBuilder>(operand_builder: O, today: NaiveDate) -> Option<Operand> {
+        OperandBuilder::build(
+            operand_builder,
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &today,
+            &ExchangeRates::for_tests(),
+        ).expect("Can build operand")
+    }
+
+    #[test]
+    fn repayment_of_the_current_period() {
+        let ex = ExchangeRates::for_tests();
+
+        // Until it is paid, the repayment is balanced by the loan's predicted balance, the remaining amount does not change
+        assert_eq!(build(loan(), mkdate(10, 1)).map(|operand| operand.amount), Some(ex.euro("410")));
+        assert_eq!(build(LoanRepayment { account: loan() }, mkdate(10, 1)), Some(Operand {
+            name: "Car loan".to_string(),
+            amount: ex.euro("-410"),
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(false)),
+                ("Date".to_string(), IllustrationValue::Date(mkdate(10, 5))),
+            ],
+        }));
+        assert_eq!(build(LoanRepayment { account: loan() }, mkdate(11, 1)), None);
+
+        // The repayment of October was not recorded, the balance at the start of November follows the schedule
+        assert_eq!(build(loan(), mkdate(11, 1)).map(|operand| operand.amount), Some(ex.euro("0")));
+    }
+
+    #[test]
+    fn repayment_already_recorded() {
+        let ex = ExchangeRates::for_tests();
+        assert_eq!(build(LoanRepayment { account: loan() }, mkdate(9, 10)), Some(Operand {
+            name: "Car loan".to_string(),
+            amount: ex.euro("0"),
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(true)),
+                ("Date".to_string(), IllustrationValue::Date(mkdate(9, 5))),
+            ],
+        }));
+    }
+}
@@ -0,0 +1,233 @@
+use super::{AccountGetter, AccountJson, Figure, ACCOUNT_DIR};
+use crate::amounts::currency_table::CurrencyTable;
+use crate::error::{ChoError, Context};
+use crate::vault::{to_json_like, write_atomically, Vault};
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::fs::{create_dir_all, read_to_string};
+use std::path::Path;
+
+/// What recording an amount changed in the account
+#[derive(Debug, Eq, PartialEq)]
+pub enum RecordedAmount {
+    /// The account file did not exist
+    NewAccount,
+    Added,
+    /// The same amount was already recorded on that date
+    AlreadyRecorded,
+    Replaced(Figure),
+}
+
+/// Record the amount of an account on a date. An amount already recorded on that date is only replaced when
+/// `overwrite` is set. The account file is created when no account has that name, which requires its currency.
+pub fn record_amount<V: Vault>(
+    vault: &V,
+    name: &str,
+    figure: Figure,
+    date: &NaiveDate,
+    currency: Option<&str>,
+    overwrite: bool,
+) -> Result<RecordedAmount, ChoError> {
+    let accounts_directory = vault.path().join(ACCOUNT_DIR);
+    let existing_account = if accounts_directory.is_dir() {
+        AccountGetter::read_account_files(vault)?.into_iter().find(|(_, account)| account.name == name)
+    } else {
+        None
+    };
+
+    let Some((path, account)) = existing_account else {
+        let currency = currency.ok_or(format!(
+            "No account is named \"{}\", give its currency with --currency to create it", name
+        ))?;
+        CurrencyTable::from_vault(vault)?.get(&currency.to_string())?;
+        let path = accounts_directory.join(account_file_name(name)?);
+        if path.exists() {
+            return Err(format!("Could not create account \"{}\": {} already exists", name, path.display()).into());
+        }
+        create_dir_all(&accounts_directory)
+            .map_err(|source| ChoError::File { path: accounts_directory.clone(), source })
+            .context(|| "Could not create the Accounts directory")?;
+        let account = json!({
+            "name": name,
+            "currency": currency,
+            "amounts": [{"date": date.to_string(), "amount": figure.to_string()}]
+        });
+        write_account_file(&path, account, None)?;
+        return Ok(RecordedAmount::NewAccount);
+    };
+
+    if let Some(currency) = currency.filter(|currency| *currency != account.currency) {
+        return Err(format!("Account \"{}\" is in {}, not in {}", name, account.currency, currency).into());
+    }
+
+    let recorded_amount = match account.recorded_amount(date) {
+        None => RecordedAmount::Added,
+        Some(recorded) if recorded == figure => return Ok(RecordedAmount::AlreadyRecorded),
+        Some(recorded) if overwrite => RecordedAmount::Replaced(recorded),
+        Some(recorded) => {
+            return Err(format!(
+                "{} is already recorded on {}, use --overwrite to replace it with {}", recorded, date, figure
+            ).into())
+        }
+    };
+    write_amounts(&path, &[(*date, figure)])?;
+    Ok(recorded_amount)
+}
+
+/// The file of a new account is named after it, so the name must be a file name of the Accounts directory
+fn account_file_name(name: &str) -> Result<String, String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\', '\0']) {
+        return Err(format!(
+            "\"{}\" cannot be the name of a new account: it must not be empty, start with a dot or contain / or \\",
+            name
+        ));
+    }
+    Ok(format!("{}.json", name))
+}
+
+/// Write the amounts into the "amounts" list of the account file, keeping it in chronological order. An amount
+/// replaces the one already recorded on the same date. The rest of the file is written back as it was read, with the
+/// same indentation.
+pub fn write_amounts(path: &Path, amounts: &[(NaiveDate, Figure)]) -> Result<(), ChoError> {
+    let path_str = path.to_str().unwrap_or("(unable to get filename)");
+    let content = read_to_string(path)
+        .map_err(|source| ChoError::File { path: path.to_path_buf(), source })
+        .context(|| "Could not read account file")?;
+    let mut account: Value = serde_json::from_str(&content)
+        .context(|| format!("Could not read account file {}", path_str))?;
+    let items = account
+        .get_mut("amounts")
+        .and_then(Value::as_array_mut)
+        .ok_or(format!("Account file {} has no amounts list", path_str))?;
+
+    for (date, figure) in amounts {
+        let date = date.to_string();
+        let item = json!({"date": date, "amount": figure.to_string()});
+        let position = items.partition_point(|item| item["date"].as_str().is_some_and(|item_date| *item_date < *date));
+        match items.get_mut(position) {
+            Some(existing_item) if existing_item["date"].as_str() == Some(&date) => *existing_item = item,
+            _ => items.insert(position, item),
+        }
+    }
+
+    serde_json::from_value::<AccountJson>(account.clone())
+        .context(|| format!("Account file {} would not be valid", path_str))?;
+    write_account_file(path, account, Some(&content))
+}
+
+fn write_account_file(path: &Path, account: Value, previous_content: Option<&str>) -> Result<(), ChoError> {
+    let content = to_json_like(&account, previous_content)?;
+    write_atomically(path, &content).context(|| "Could not write account file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::QueriableAccount;
+    use crate::vault::VaultImpl;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use std::fs::{create_dir, write};
+
+    fn mkdate(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).expect("Can create date")
+    }
+
+    fn read_account(vault: &VaultImpl, file_name: &str) -> Value {
+        serde_json::from_str(&read_to_string(vault.path().join(ACCOUNT_DIR).join(file_name)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn write_into_account_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("current.json");
+        write(&path, r#"{"name": "Current", "currency": "EUR", "kind": "asset", "amounts": [
+            {"date": "2025-09-01", "amount": 1000},
+            {"date": "2025-09-05", "amount": 800}
+        ]}"#).unwrap();
+
+        write_amounts(&path, &[(mkdate(3), dec!(900.25)), (mkdate(7), dec!(-10)), (mkdate(5), dec!(810))])
+            .expect("Can write amounts");
+
+        let account: Value = serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(account, json!({"name": "Current", "currency": "EUR", "kind": "asset", "amounts": [
+            {"date": "2025-09-01", "amount": 1000},
+            {"date": "2025-09-03", "amount": "900.25"},
+            {"date": "2025-09-05", "amount": "810"},
+            {"date": "2025-09-07", "amount": "-10"}
+        ]}));
+    }
+
+    #[test]
+    fn record() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+
+        assert_eq!(
+            record_amount(&vault, "Current", dec!(1000), &mkdate(1), None, false),
+            Err("No account is named \"Current\", give its currency with --currency to create it".into())
+        );
+        assert_eq!(record_amount(&vault, "Current", dec!(1000), &mkdate(1), Some("EUR"), false), Ok(RecordedAmount::NewAccount));
+        assert_eq!(record_amount(&vault, "Current", dec!(700), &mkdate(10), None, false), Ok(RecordedAmount::Added));
+        assert_eq!(record_amount(&vault, "Current", dec!(800), &mkdate(5), Some("EUR"), false), Ok(RecordedAmount::Added));
+        assert_eq!(
+            read_account(&vault, "Current.json"),
+            json!({"name": "Current", "currency": "EUR", "amounts": [
+                {"date": "2025-09-01", "amount": "1000"},
+                {"date": "2025-09-05", "amount": "800"},
+                {"date": "2025-09-10", "amount": "700"}
+            ]})
+        );
+
+        let (_, account) = AccountGetter::find_account_file(&vault, "Current").unwrap();
+        assert_eq!(account.amount_at(&mkdate(7)).map(|found_amount| found_amount.figure), Ok(dec!(800)));
+    }
+
+    #[test]
+    fn invalid_new_account() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+
+        for name in ["../Current", "Cards/Visa", ".hidden", ""] {
+            assert_eq!(
+                record_amount(&vault, name, dec!(1000), &mkdate(1), Some("EUR"), false),
+                Err(format!("\"{}\" cannot be the name of a new account: it must not be empty, start with a dot or contain / or \\", name).into())
+            );
+        }
+        assert!(record_amount(&vault, "Current", dec!(1000), &mkdate(1), Some("EUE"), false)
+            .is_err_and(|error| error.to_string().starts_with("Unsupported currency: EUE.")));
+        assert!(!vault.path().join(ACCOUNT_DIR).exists());
+    }
+
+    #[test]
+    fn unwritable_accounts_directory() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+        write(vault.path().join(ACCOUNT_DIR), "").unwrap();
+
+        let error = record_amount(&vault, "Current", dec!(1000), &mkdate(1), Some("EUR"), false).unwrap_err();
+        assert_eq!(error.exit_code(), 3, "{}", error);
+    }
+
+    #[test]
+    fn existing_date() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+        create_dir(vault.path().join(ACCOUNT_DIR)).unwrap();
+        write(
+            vault.path().join(ACCOUNT_DIR).join("livret.json"),
+            r#"{"name": "Livret A", "currency": "EUR", "amounts": [{"date": "2025-09-01", "amount": 1000}]}"#,
+        ).unwrap();
+
+        assert_eq!(
+            record_amount(&vault, "Livret A", dec!(1200), &mkdate(1), None, false),
+            Err("1000 is already recorded on 2025-09-01, use --overwrite to replace it with 1200".into())
+        );
+        assert_eq!(
+            record_amount(&vault, "Livret A", dec!(1000), &mkdate(2), Some("JPY"), false),
+            Err("Account \"Livret A\" is in EUR, not in JPY".into())
+        );
+        assert_eq!(record_amount(&vault, "Livret A", dec!(1000), &mkdate(1), None, false), Ok(RecordedAmount::AlreadyRecorded));
+        assert_eq!(record_amount(&vault, "Livret A", dec!(1200), &mkdate(1), None, true), Ok(RecordedAmount::Replaced(dec!(1000))));
+        assert_eq!(
+            read_account(&vault, "livret.json")["amounts"],
+            json!([{"date": "2025-09-01", "amount": "1200"}])
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use super::{AccountGetter, AccountJson, QueriableAccount};
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::error::ChoError;
+use crate::amounts::{Add, Amount, CurrencyIdent, Mul};
+use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::remaining_operation::core_types::{GroupBuilder, Operand, OperandBuilder};
+use crate::vault::Vault;
+use chrono::{Datelike, Months, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone)]
+pub struct SavingsConfiguration {
+    /// Eg. 0.5 for 0.5% a year
+    annual_rate_percent: Decimal,
+    schedule: InterestSchedule,
+    /// Whether the interest is added to the account, where it earns interest in turn.
+    /// Otherwise it is paid out to another account.
+    #[serde(default)]
+    compounding: bool,
+}
+
+/// Interest is paid on the last day of each month, quarter or year
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum InterestSchedule {
+    #[serde(rename = "monthly")]
+    Monthly,
+    #[serde(rename = "quarterly")]
+    Quarterly,
+    #[serde(rename = "yearly")]
+    Yearly,
+}
+
+impl InterestSchedule {
+    fn months_between_payments(&self) -> u32 {
+        match self {
+            InterestSchedule::Monthly => 1,
+            InterestSchedule::Quarterly => 3,
+            InterestSchedule::Yearly => 12,
+        }
+    }
+
+    /// Payment dates between the two dates, both included
+    fn payment_dates(&self, from: &NaiveDate, until: &NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = vec![];
+        let mut month_start = from.with_day(1);
+        while let Some(start) = month_start {
+            let Some(month_end) = start.checked_add_months(Months::new(1)).and_then(|next| next.pred_opt()) else {
+                break;
+            };
+            if month_end > *until {
+                break;
+            }
+            if month_end >= *from && month_end.month() % self.months_between_payments() == 0 {
+                dates.push(month_end);
+            }
+            month_start = month_end.succ_opt();
+        }
+        dates
+    }
+}
+
+impl SavingsConfiguration {
+    fn rate_per_payment(&self) -> Decimal {
+        self.annual_rate_percent / dec!(100) * Decimal::from(self.schedule.months_between_payments()) / dec!(12)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct InterestPayment {
+    pub date: NaiveDate,
+    pub interest: Amount,
+    /// The balance the interest was computed on
+    pub balance: Amount,
+}
+
+impl AccountJson {
+    /// The interest paid between the two dates, both included. Payments are computed on the last balance
+    /// recorded before them, plus the interest projected before them when the account compounds.
+    pub fn project_interest(
+        &self,
+        from: &NaiveDate,
+        until: &NaiveDate,
+        exchange_rates: &ExchangeRates,
+    ) -> Result<Vec<InterestPayment>, ChoError> {
+        let Some(savings) = &self.savings else {
+            return Ok(vec![]);
+        };
+
+        let mut projected_interest = exchange_rates.zero(&self.currency)?;
+        let mut payments = vec![];
+        for date in savings.schedule.payment_dates(from, until) {
+            let recorded_balance = self
+                .amount_at(&date)?
+                .into_remaining_module_amount(&self.currency, exchange_rates, &date)?;
+            let balance = if savings.compounding {
+                recorded_balance.add(&projected_interest)
+            } else {
+                recorded_balance
+            };
+
+            let interest = balance.mul(&savings.rate_per_payment());
+            projected_interest = projected_interest.add(&interest);
+            payments.push(InterestPayment { date, interest, balance });
+        }
+
+        Ok(payments)
+    }
+}
+
+/// The interest a savings account earns during the current period
+pub struct SavingsInterest {
+    account: AccountJson,
+}
+
+impl OperandBuilder for SavingsInterest {
+    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
+        let current_period = period_config.period_for_date(today)?;
+        let payments = self.account.project_interest(&current_period.start_date, &current_period.end_date, exchange_rates)?;
+
+        let interest = payments
+            .iter()
+            .fold(exchange_rates.zero(&self.account.currency)?, |total, payment| total.add(&payment.interest));
+        let last_payment = payments.last();
+
+        Ok(Some(Operand {
+            name: self.account.name.clone(),
+            amount: interest,
+            illustration: vec![
+                ("Payment date".to_string(), last_payment.map(|payment| payment.date).into()),
+                (
+                    "Balance earning interest".to_string(),
+                    last_payment.map(|payment| payment.balance.clone()).into(),
+                ),
+            ],
+        }))
+    }
+}
+
+/// The savings accounts of the vault
+pub struct SavingsInterestGetter {
+    accounts: Vec<AccountJson>,
+}
+
+impl SavingsInterestGetter {
+    pub fn from_vault<V: Vault>(vault: &V) -> Result<SavingsInterestGetter, ChoError> {
+        Ok(Self::from_accounts(&AccountGetter::from_vault(vault)?))
+    }
+
+    pub fn from_accounts(accounts: &AccountGetter) -> SavingsInterestGetter {
+        SavingsInterestGetter {
+            accounts: accounts.accounts.iter().filter(|account| account.savings.is_some()).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Every interest payment between the two dates, in chronological order
+    pub fn project(
+        &self,
+        from: &NaiveDate,
+        until: &NaiveDate,
+        exchange_rates: &ExchangeRates,
+        target_currency: &CurrencyIdent,
+    ) -> Result<Vec<ProjectedInterest>, ChoError> {
+        let mut payments = vec![];
+        for account in self.accounts.iter() {
+            for payment in account.project_interest(from, until, exchange_rates)? {
+                payments.push((account.name.clone(), payment));
+            }
+        }
+        payments.sort_by_key(|(_, payment)| payment.date);
+
+        let mut accumulated = exchange_rates.zero(target_currency)?;
+        Ok(payments
+            .into_iter()
+            .map(|(account, payment)| {
+                accumulated = accumulated.add(&payment.interest);
+                ProjectedInterest { account, payment, accumulated: accumulated.clone() }
+            })
+            .collect())
+    }
+}
+
+impl GroupBuilder<SavingsInterest> for SavingsInterestGetter {
+    fn build(self) -> Result<(String, Vec<SavingsInterest>), ChoError> {
+        Ok((
+            "Savings interest".into(),
+            self.accounts.into_iter().map(|account| SavingsInterest { account }).collect(),
+        ))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ProjectedInterest {
+    pub account: String,
+    pub payment: InterestPayment,
+    /// Interest of this payment and of all the ones before it
+    pub accumulated: Amount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use crate::remaining_operation::core_types::IllustrationValue;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn mkdate(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("Can create date")
+    }
+
+    fn savings_account(schedule: &str, compounding: bool) -> AccountJson {
+        serde_json::from_value(json!({
+            "name": "Savings",
+            "currency": "EUR",
+            "amounts": [
+                {"date": "2025-01-01", "amount": 12000},
+                {"date": "2025-05-10", "amount": 24000}
+            ],
+            "savings": {"annual_rate_percent": "1.5", "schedule": schedule, "compounding": compounding}
+        })).expect("Can parse account")
+    }
+
+    #[test]
+    fn payment_dates() {
+        assert_eq!(
+            InterestSchedule::Quarterly.payment_dates(&mkdate(2025, 3, 31), &mkdate(2025, 12, 30)),
+            vec![mkdate(2025, 3, 31), mkdate(2025, 6, 30), mkdate(2025, 9, 30)]
+        );
+        assert_eq!(
+            InterestSchedule::Monthly.payment_dates(&mkdate(2024, 1, 15), &mkdate(2024, 3, 1)),
+            vec![mkdate(2024, 1, 31), mkdate(2024, 2, 29)]
+        );
+        assert_eq!(InterestSchedule::Yearly.payment_dates(&mkdate(2025, 1, 1), &mkdate(2025, 12, 30)), vec![]);
+    }
+
+    #[test]
+    fn compounding_and_payout() {
+        let ex = ExchangeRates::for_tests();
+
+        let paid_out = savings_account("monthly", false)
+            .project_interest(&mkdate(2025, 3, 1), &mkdate(2025, 5, 31), &ex)
+            .expect("Can project interest");
+        assert_eq!(
+            paid_out.iter().map(|payment| payment.interest.clone()).collect::<Vec<Amount>>(),
+            vec![ex.euro("15"), ex.euro("15"), ex.euro("30")]
+        );
+
+        let compounded = savings_account("monthly", true)
+            .project_interest(&mkdate(2025, 3, 1), &mkdate(2025, 5, 31), &ex)
+            .expect("Can project interest");
+        assert_eq!(compounded, vec![
+            InterestPayment { date: mkdate(2025, 3, 31), interest: ex.euro("15"), balance: ex.euro("12000") },
+            InterestPayment { date: mkdate(2025, 4, 30), interest: ex.euro("15.02"), balance: ex.euro("12015") },
+            InterestPayment { date: mkdate(2025, 5, 31), interest: ex.euro("30.04"), balance: ex.euro("24030.02") },
+        ]);
+    }
+
+    #[test]
+    fn interest_of_the_current_period() {
+        let ex = ExchangeRates::for_tests();
+        let period_configuration = PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {});
+        let build = |today: NaiveDate| OperandBuilder::build(
+            SavingsInterest { account: savings_account("quarterly", false) },
+            &period_configuration,
+            &today,
+            &ex,
+        ).expect("Can build operand");
+
+        assert_eq!(build(mkdate(2025, 6, 5)), Some(Operand {
+            name: "Savings".to_string(),
+            amount: ex.euro("90"),
+            illustration: vec![
+                ("Payment date".to_string(), IllustrationValue::Date(mkdate(2025, 6, 30))),
+                ("Balance earning interest".to_string(), IllustrationValue::Amount(ex.euro("24000"))),
+            ],
+        }));
+        assert_eq!(build(mkdate(2025, 7, 5)), Some(Operand {
+            name: "Savings".to_string(),
+            amount: ex.euro("0"),
+            illustration: vec![
+                ("Payment date".to_string(), IllustrationValue::NullDate),
+                ("Balance earning interest".to_string(), IllustrationValue::NullAmount),
+            ],
+        }));
+    }
+}
@@ -0,0 +1,165 @@
+use super::{AccountGetter, AccountJson, Figure};
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::Amount;
+use crate::error::ChoError;
+use crate::period::Period;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// A movement on an account, signed like the amounts recorded for it.
+/// Once an account records transactions, every movement from the date of the first one on is expected to be recorded.
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone)]
+pub struct Transaction {
+    pub(super) date: NaiveDate,
+    #[serde(deserialize_with = "super::deserialize_figure")]
+    pub(super) amount: Figure,
+    #[serde(default)]
+    pub(super) payee: Option<String>,
+    #[serde(default)]
+    pub(super) memo: Option<String>,
+}
+
+/// A transaction of the current period, shown on the remaining screen
+#[derive(Debug, Eq, PartialEq)]
+pub struct NotableTransaction {
+    pub account: String,
+    pub date: NaiveDate,
+    /// As a balance change, liabilities count negatively
+    pub amount: Amount,
+    pub description: String,
+}
+
+impl AccountJson {
+    /// Whether the transactions give every movement of the account after that day
+    pub(super) fn transactions_cover(&self, day: &NaiveDate) -> bool {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.date)
+            .min()
+            .is_some_and(|first_date| day.succ_opt().is_some_and(|next_day| first_date <= next_day))
+    }
+
+    /// The sum of the transactions after a day, until another one included
+    pub(super) fn transactions_between(&self, after: &NaiveDate, until: &NaiveDate) -> Figure {
+        self.transactions
+            .iter()
+            .filter(|transaction| transaction.date > *after && transaction.date <= *until)
+            .map(|transaction| transaction.amount)
+            .sum()
+    }
+
+    /// The last day on or before the date for which the balance is known exactly,
+    /// either recorded or computed from the transactions that follow a recorded amount
+    pub(super) fn last_known_date(&self, date: &NaiveDate) -> Option<NaiveDate> {
+        let last_recorded = self.amounts.iter().rev().find(|item| item.date <= *date)?.date;
+        if !self.transactions_cover(&last_recorded) {
+            return Some(last_recorded);
+        }
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.date)
+            .filter(|transaction_date| transaction_date <= date)
+            .max()
+            .filter(|transaction_date| *transaction_date > last_recorded)
+            .or(Some(last_recorded))
+    }
+}
+
+impl AccountGetter {
+    /// The transactions of the period with the largest amounts, at most `count` of them for each account
+    pub fn largest_transactions(&self, period: &Period, count: usize, exchange_rates: &ExchangeRates) -> Result<Vec<NotableTransaction>, ChoError> {
+        let mut notable_transactions = vec![];
+        for account in self.accounts.iter() {
+            let mut transactions: Vec<&Transaction> = account
+                .transactions
+                .iter()
+                .filter(|transaction| period.contains(&transaction.date))
+                .collect();
+            transactions.sort_by_key(|transaction| std::cmp::Reverse(transaction.amount.abs()));
+
+            for transaction in transactions.into_iter().take(count) {
+                let description: Vec<&str> = [&transaction.payee, &transaction.memo]
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str)
+                    .collect();
+                notable_transactions.push(NotableTransaction {
+                    account: account.name.clone(),
+                    date: transaction.date,
+                    amount: account.balance(transaction.amount, &transaction.date, exchange_rates)?,
+                    description: description.join(" - "),
+                });
+            }
+        }
+        Ok(notable_transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{FoundAmount, QueriableAccount};
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    fn account() -> AccountJson {
+        serde_json::from_value(json!({
+            "name": "Current",
+            "currency": "EUR",
+            "amounts": [{"date": "2025-09-01", "amount": 1000}, {"date": "2025-09-20", "amount": 90}],
+            "transactions": [
+                {"date": "2025-09-02", "amount": -800, "payee": "Landlord", "memo": "Rent"},
+                {"date": "2025-09-05", "amount": "-12.40", "payee": "Bakery"},
+                {"date": "2025-09-05", "amount": "-97.60"},
+                {"date": "2025-09-21", "amount": 2000, "memo": "Salary"}
+            ]
+        })).expect("Can parse account")
+    }
+
+    #[test]
+    fn balance_between_recorded_amounts() {
+        let account = account();
+        assert_eq!(account.amount_at(&mkdate(9, 3)), Ok(FoundAmount { figure: dec!(200), estimated: false }));
+        assert_eq!(account.amount_at(&mkdate(9, 10)), Ok(FoundAmount { figure: dec!(90), estimated: false }));
+        // The recorded amount is kept as it is
+        assert_eq!(account.amount_at(&mkdate(9, 20)), Ok(FoundAmount { figure: dec!(90), estimated: false }));
+        assert_eq!(account.amount_at(&mkdate(9, 25)), Ok(FoundAmount { figure: dec!(2090), estimated: false }));
+
+        assert_eq!(account.last_known_date(&mkdate(9, 10)), Some(mkdate(9, 5)));
+        assert_eq!(account.last_known_date(&mkdate(9, 30)), Some(mkdate(9, 21)));
+    }
+
+    #[test]
+    fn transactions_start_after_the_recorded_amount() {
+        let mut account = account();
+        account.transactions.retain(|transaction| transaction.date > mkdate(9, 20));
+        // Nothing tells what happened between the 1st and the 21st
+        assert_eq!(account.amount_at(&mkdate(9, 10)), Ok(FoundAmount { figure: dec!(1000), estimated: true }));
+        assert_eq!(account.amount_at(&mkdate(9, 25)), Ok(FoundAmount { figure: dec!(2090), estimated: false }));
+    }
+
+    #[test]
+    fn largest_transactions() {
+        let ex = ExchangeRates::for_tests();
+        let period = Period { start_date: mkdate(9, 1), end_date: mkdate(9, 20) };
+        assert_eq!(AccountGetter::new(vec![account()]).largest_transactions(&period, 2, &ex), Ok(vec![
+            NotableTransaction {
+                account: "Current".to_string(),
+                date: mkdate(9, 2),
+                amount: ex.euro("-800"),
+                description: "Landlord - Rent".to_string(),
+            },
+            NotableTransaction {
+                account: "Current".to_string(),
+                date: mkdate(9, 5),
+                amount: ex.euro("-97.60"),
+                description: "".to_string(),
+            },
+        ]));
+    }
+}
@@ -3,7 +3,11 @@ use std::fmt::{Debug, Display, Formatter};
 use crate::amounts::amount::ImmutableAmount;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::Deserialize;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use crate::amounts::currency_table::{CurrencyDefinition, SignPlacement};
+
+pub mod currency_table;
 
 pub type Figure = Decimal;
 pub type CurrencyIdent = String;
@@ -14,77 +18,191 @@ pub type CurrencyIdent = String;
 //      Ian told me that Rc could be used for this use-case
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Currency {
+    pub code: CurrencyIdent,
     pub rate: Figure,
     pub sign: String,
+    pub minor_units: Option<u32>,
+    pub sign_placement: SignPlacement,
+}
+
+impl Currency {
+    pub fn new(definition: &CurrencyDefinition, rate: Figure) -> Currency {
+        Currency {
+            code: definition.code.clone(),
+            rate,
+            sign: definition.sign.clone(),
+            minor_units: definition.minor_units,
+            sign_placement: definition.sign_placement,
+        }
+    }
 }
 
 pub mod exchange_rates {
     use super::{Amount, Currency, CurrencyIdent, Figure, RawAmount};
     use crate::amounts::amount::ImmutableAmount;
+    use crate::amounts::currency_table::CurrencyTable;
+    use crate::error::ChoError;
+    use crate::vault::VaultReadable;
     use std::collections::HashMap;
+    use chrono::NaiveDate;
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
+    use serde::Deserialize;
+
+    /// Rates that came into effect on `date`. A currency absent from a table keeps the rate
+    /// it had in the previous tables.
+    #[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+    pub struct DatedExchangeRates {
+        pub date: NaiveDate,
+        pub rates: HashMap<CurrencyIdent, Figure>,
+    }
+
+    pub type ExchangeRatesVaultValue = Vec<DatedExchangeRates>;
+    impl VaultReadable for ExchangeRatesVaultValue {
+        const KEY: &'static str = "exchange_rates";
+    }
 
     #[derive(Clone)]
-    pub struct ExchangeRates {
+    struct DatedCurrencies {
+        date: NaiveDate,
         currencies: HashMap<CurrencyIdent, Currency>,
     }
 
+    #[derive(Clone)]
+    pub struct ExchangeRates {
+        // Rates that apply at any date, they take precedence over the history
+        overrides: HashMap<CurrencyIdent, Currency>,
+        // Sorted by date
+        history: Vec<DatedCurrencies>,
+    }
+
     impl ExchangeRates {
-        pub fn from_indent_and_rates(rates: Vec<(CurrencyIdent, Figure)>) -> Result<ExchangeRates, String> {
-            let currencies: Result<HashMap<CurrencyIdent, Currency>, String> = rates
+        pub fn from_indent_and_rates(
+            rates: Vec<(CurrencyIdent, Figure)>,
+            currency_table: &CurrencyTable,
+        ) -> Result<ExchangeRates, ChoError> {
+            ExchangeRates::from_history_and_rates(vec![], rates, currency_table)
+        }
+
+        /// `rates` override the history: they are used whatever the date of the amount
+        pub fn from_history_and_rates(
+            history: ExchangeRatesVaultValue,
+            rates: Vec<(CurrencyIdent, Figure)>,
+            currency_table: &CurrencyTable,
+        ) -> Result<ExchangeRates, ChoError> {
+            let to_currencies = |rates: Vec<(CurrencyIdent, Figure)>| rates
                 .into_iter()
                 .map(|(ident, rate)| {
-                    let sign = match ident.as_str() {
-                        "EUR" => "€".to_string(),
-                        "JPY" => "¥".to_string(),
-                        _ => return Err(format!(
-                            "Unsupported currency: {}. We support only EUR and JPY for now.", ident)),
-                    };
-
-                    Ok((ident, Currency { rate, sign }))
+                    let currency = Currency::new(currency_table.get(&ident)?, rate);
+                    Ok((ident, currency))
                 })
-                .collect();
+                .collect::<Result<HashMap<CurrencyIdent, Currency>, String>>();
+
+            let mut history = history
+                .into_iter()
+                .map(|dated_rates| Ok(DatedCurrencies {
+                    date: dated_rates.date,
+                    currencies: to_currencies(dated_rates.rates.into_iter().collect())?,
+                }))
+                .collect::<Result<Vec<DatedCurrencies>, String>>()?;
+            history.sort_by_key(|dated_currencies| dated_currencies.date);
+
+            Ok(ExchangeRates {
+                overrides: to_currencies(rates)?,
+                history,
+            })
+        }
 
-            currencies.map(|rate| ExchangeRates { currencies: rate })
+        /// The most recent rate known for the currency
+        fn get_currency(&self, ident: &CurrencyIdent) -> Result<&Currency, ChoError> {
+            self.overrides
+                .get(ident)
+                .or_else(|| self.history.iter().rev().find_map(|dated| dated.currencies.get(ident)))
+                .ok_or_else(|| ChoError::UnknownCurrency(ident.clone()))
         }
-        fn get_currency(&self, ident: &CurrencyIdent) -> Result<&Currency, String> {
-            self.currencies
+
+        /// The rate in effect on `date`. Dates before the first rate known for the currency use that first rate.
+        fn get_currency_at(&self, ident: &CurrencyIdent, date: &NaiveDate) -> Result<&Currency, ChoError> {
+            self.overrides
                 .get(ident)
-                .ok_or(format!("Could not find currency ident: {}", ident))
+                .or_else(|| self.history
+                    .iter()
+                    .rev()
+                    .filter(|dated| dated.date <= *date)
+                    .find_map(|dated| dated.currencies.get(ident)))
+                .or_else(|| self.history.iter().find_map(|dated| dated.currencies.get(ident)))
+                .ok_or_else(|| ChoError::UnknownCurrency(ident.clone()))
         }
 
-        fn get_currency_from_sign(&self, sign: &str) -> Result<&Currency, String> {
-            self.currencies.values().find(|c| c.sign == sign).ok_or("Could not find currency".to_string())
+        fn get_ident_from_sign(&self, sign: &str) -> Result<&CurrencyIdent, ChoError> {
+            let mut matching_idents: Vec<&CurrencyIdent> = self.overrides
+                .iter()
+                .chain(self.history.iter().flat_map(|dated| dated.currencies.iter()))
+                .filter(|(_, currency)| currency.sign == sign)
+                .map(|(ident, _)| ident)
+                .collect();
+            matching_idents.sort();
+            matching_idents.dedup();
+
+            match matching_idents.as_slice() {
+                [ident] => Ok(ident),
+                _ => Err(ChoError::UnknownCurrencySign {
+                    sign: sign.to_string(),
+                    matching: matching_idents.into_iter().cloned().collect(),
+                }),
+            }
         }
 
         pub fn new_amount(
             &self,
             currency_ident: &CurrencyIdent,
             figure: Figure,
-        ) -> Result<Amount, String> {
+        ) -> Result<Amount, ChoError> {
             let currency = self.get_currency(currency_ident)?;
             self.new_amount_from_currency(currency, figure)
         }
 
+        /// Create an amount converted at the rate in effect on `date`
+        pub fn new_amount_at(
+            &self,
+            currency_ident: &CurrencyIdent,
+            figure: Figure,
+            date: &NaiveDate,
+        ) -> Result<Amount, ChoError> {
+            let currency = self.get_currency_at(currency_ident, date)?;
+            self.new_amount_from_currency(currency, figure)
+        }
+
         fn new_amount_from_currency(
             &self,
             currency: &Currency,
             figure: Figure,
-        ) -> Result<Amount, String> {
+        ) -> Result<Amount, ChoError> {
             Ok(Amount {
                 immutable_amount: ImmutableAmount::new(currency, figure)
             })
         }
 
-        pub fn new_amount_from_raw_amount(
+        /// Create an amount converted at the rate in effect on `date`
+        pub fn new_amount_from_raw_amount_at(
             &self,
             raw_amount: &RawAmount,
-        ) -> Result<Amount, String>{
-           self.new_amount_from_currency(self.get_currency_from_sign(&raw_amount.sign)?, raw_amount.figure)
+            date: &NaiveDate,
+        ) -> Result<Amount, ChoError>{
+            self.new_amount_at(self.get_ident_from_sign(&raw_amount.sign)?, raw_amount.figure, date)
         }
 
-        pub fn zero(&self, currency_ident: &CurrencyIdent) -> Result<Amount, String> {
+        /// Convert an amount into another currency at the rate in effect on `date`
+        pub fn convert_at(
+            &self,
+            amount: &Amount,
+            currency_ident: &CurrencyIdent,
+            date: &NaiveDate,
+        ) -> Result<Amount, ChoError> {
+            Ok(amount.convert(self.get_currency_at(currency_ident, date)?))
+        }
+
+        pub fn zero(&self, currency_ident: &CurrencyIdent) -> Result<Amount, ChoError> {
             self.new_amount(currency_ident, dec!(0))
         }
     }
@@ -95,7 +213,7 @@ pub mod exchange_rates {
             ExchangeRates::from_indent_and_rates(vec![
                 ("EUR".to_string(), dec!(1)),
                 ("JPY".to_string(), dec!(2))
-            ]).expect("Can create exchange rates")
+            ], &CurrencyTable::iso_4217()).expect("Can create exchange rates")
         }
         
         pub fn yen(&self, figure: &str) -> Amount {
@@ -125,10 +243,14 @@ mod amount {
 
     impl ImmutableAmount {
         pub fn new(currency: &Currency, figure: Figure) -> Self {
+            let figure = match currency.minor_units {
+                Some(minor_units) => figure
+                    .round_dp_with_strategy(minor_units, rust_decimal::RoundingStrategy::MidpointNearestEven),
+                None => figure,
+            };
             Self {
                 currency: currency.clone(),
-                figure: figure
-                    .round_dp_with_strategy(2, rust_decimal::RoundingStrategy::MidpointNearestEven),
+                figure,
             }
         }
 
@@ -149,7 +271,24 @@ pub struct Amount {
 
 impl Display for Amount {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}", self.immutable_amount.currency().sign, self.immutable_amount.figure())
+        let sign = &self.immutable_amount.currency().sign;
+        let figure = self.immutable_amount.figure();
+        // Signs made of letters (eg. "CHF", "kr") need a space to be told apart from the figure
+        let separator = if sign.chars().any(char::is_alphabetic) { " " } else { "" };
+        match self.immutable_amount.currency().sign_placement {
+            SignPlacement::Before => write!(f, "{}{}{}", sign, separator, figure),
+            SignPlacement::After => write!(f, "{}{}{}", figure, separator, sign),
+        }
+    }
+}
+
+/// Written as {"currency": "EUR", "figure": "12.50"}, the figure as a string to keep every decimal
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut amount = serializer.serialize_struct("Amount", 2)?;
+        amount.serialize_field("currency", &self.immutable_amount.currency().code)?;
+        amount.serialize_field("figure", self.immutable_amount.figure())?;
+        amount.end()
     }
 }
 
@@ -161,6 +300,12 @@ impl Debug for Amount {
 
 impl Amount {
     fn convert(&self, target_currency: &Currency) -> Amount {
+        // Amounts of the same currency created at different dates carry different rates,
+        // they must not be converted into one another
+        if self.immutable_amount.currency().code == target_currency.code {
+            return self.clone();
+        }
+
         let exchange_rate = {
             let target_currency_value = target_currency.rate;
             let from_currency_value = self.immutable_amount.currency().rate;
@@ -194,6 +339,10 @@ pub trait Div<T> {
     fn div(&self, divisor: &T) -> Amount;
 }
 
+pub trait Mul<T> {
+    fn mul(&self, factor: &T) -> Amount;
+}
+
 impl Add<Amount> for Amount {
     fn add(&self, other_amount: &Amount) -> Amount {
         let other_amount_converted = other_amount.convert(self.immutable_amount.currency());
@@ -244,6 +393,17 @@ impl Div<Decimal> for Amount {
     }
 }
 
+impl Mul<Decimal> for Amount {
+    fn mul(&self, factor: &Decimal) -> Amount {
+        Amount {
+            immutable_amount: ImmutableAmount::new(
+                self.immutable_amount.currency(),
+                self.immutable_amount.figure() * factor
+            )
+        }
+    }
+}
+
 impl Amount {
     pub fn maximum(amount_a: &Amount, amount_b: &Amount) -> Amount {
         let amount_b_converted = amount_b.convert(amount_a.immutable_amount.currency());
@@ -260,6 +420,14 @@ impl Amount {
         self.immutable_amount.figure() < &dec!(0)
     }
     
+    /// The amount as it would be written in a vault line
+    pub fn to_raw_amount(&self) -> RawAmount {
+        RawAmount {
+            sign: self.immutable_amount.currency().sign.clone(),
+            figure: *self.immutable_amount.figure(),
+        }
+    }
+
     pub fn flip_sign(&self) -> Amount {
         Amount {
             immutable_amount: ImmutableAmount::new(
@@ -284,4 +452,137 @@ impl RawAmount {
             figure: Decimal::from_str_exact(figure).expect("can build a decimal from passed string"),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amounts::currency_table::{CurrencyDefinition, CurrencyTable};
+    use crate::amounts::exchange_rates::{ExchangeRates, ExchangeRatesVaultValue};
+    use crate::error::ChoError;
+    use crate::vault::{VaultImpl, VaultReadable};
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn exchange_rates() -> ExchangeRates {
+        let currency_table = CurrencyTable::iso_4217().with_custom_currencies(vec![
+            CurrencyDefinition {
+                code: "SEK".to_string(),
+                sign: "kr".to_string(),
+                minor_units: Some(2),
+                sign_placement: SignPlacement::After,
+            }
+        ]);
+        ExchangeRates::from_indent_and_rates(vec![
+            ("USD".to_string(), dec!(1)),
+            ("JPY".to_string(), dec!(150)),
+            ("BHD".to_string(), dec!(0.376)),
+            ("CHF".to_string(), dec!(0.9)),
+            ("SEK".to_string(), dec!(10.5)),
+        ], &currency_table).expect("Can create exchange rates")
+    }
+
+    #[test]
+    fn rounding_follows_minor_units() {
+        let ex = exchange_rates();
+        assert_eq!(ex.new_amount(&"JPY".to_string(), dec!(100.5)).unwrap().to_string(), "¥100");
+        assert_eq!(ex.new_amount(&"USD".to_string(), dec!(1.005)).unwrap().to_string(), "$1.00");
+        assert_eq!(ex.new_amount(&"BHD".to_string(), dec!(1.0005)).unwrap().to_string(), "BHD 1.000");
+    }
+
+    #[test]
+    fn sign_placement() {
+        let ex = exchange_rates();
+        assert_eq!(ex.new_amount(&"CHF".to_string(), dec!(12)).unwrap().to_string(), "CHF 12");
+        assert_eq!(ex.new_amount(&"SEK".to_string(), dec!(12)).unwrap().to_string(), "12 kr");
+    }
+
+    #[test]
+    fn unsupported_currency() {
+        assert!(ExchangeRates::from_indent_and_rates(
+            vec![("ABC".to_string(), dec!(1))],
+            &CurrencyTable::iso_4217(),
+        ).is_err());
+    }
+
+    #[test]
+    fn raw_amount_with_ambiguous_sign() {
+        let currency_table = CurrencyTable::iso_4217().with_custom_currencies(vec![
+            CurrencyDefinition {
+                code: "CNY".to_string(),
+                sign: "¥".to_string(),
+                minor_units: Some(2),
+                sign_placement: SignPlacement::Before,
+            }
+        ]);
+        let ex = ExchangeRates::from_indent_and_rates(vec![
+            ("JPY".to_string(), dec!(150)),
+            ("CNY".to_string(), dec!(7)),
+        ], &currency_table).expect("Can create exchange rates");
+
+        assert_eq!(
+            ex.new_amount_from_raw_amount_at(&RawAmount::yen("100"), &NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
+            Err(ChoError::UnknownCurrencySign { sign: "¥".to_string(), matching: vec!["CNY".to_string(), "JPY".to_string()] })
+        );
+    }
+
+    fn history() -> ExchangeRates {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "exchange_rates": [
+                {"date": "2024-06-01", "rates": {"JPY": "170"}},
+                {"date": "2024-01-01", "rates": {"EUR": "1", "JPY": "160"}},
+                {"date": "2024-09-01", "rates": {"USD": "1.1"}}
+            ]
+        }));
+        let history = ExchangeRatesVaultValue::from_vault(&vault).expect("Can read exchange rates");
+        ExchangeRates::from_history_and_rates(history, vec![], &CurrencyTable::iso_4217())
+            .expect("Can create exchange rates")
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn dated_rates() {
+        let ex = history();
+        let euros = |date: NaiveDate| ex.new_amount_at(&"EUR".to_string(), dec!(10), &date).unwrap();
+        let yens = |date: NaiveDate| ex.new_amount_at(&"JPY".to_string(), dec!(1700), &date).unwrap();
+
+        // Rate in effect on the date
+        assert_eq!(euros(date(2024, 7, 1)).add(&yens(date(2024, 7, 1))).to_string(), "€20.00");
+        assert_eq!(euros(date(2024, 7, 1)).add(&yens(date(2024, 3, 1))).to_string(), "€20.62");
+        // Before the first rate known for the currency
+        assert_eq!(euros(date(2024, 7, 1)).add(&yens(date(2023, 3, 1))).to_string(), "€20.62");
+        assert_eq!(euros(date(2023, 3, 1)).add(&ex.new_amount_at(&"USD".to_string(), dec!(11), &date(2023, 3, 1)).unwrap()).to_string(), "€20.00");
+        // Without date, the most recent rate is used
+        assert_eq!(euros(date(2024, 7, 1)).add(&ex.new_amount(&"JPY".to_string(), dec!(1700)).unwrap()).to_string(), "€20.00");
+    }
+
+    #[test]
+    fn same_currency_at_different_rates_is_not_converted() {
+        let ex = history();
+        let old_yens = ex.new_amount_at(&"JPY".to_string(), dec!(1000), &date(2024, 2, 1)).unwrap();
+        let new_yens = ex.new_amount_at(&"JPY".to_string(), dec!(1000), &date(2024, 7, 1)).unwrap();
+
+        assert_eq!(new_yens.sub(&old_yens).to_string(), "¥0");
+    }
+
+    #[test]
+    fn rates_override_history() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "exchange_rates": [
+                {"date": "2024-01-01", "rates": {"EUR": "1", "JPY": "160"}}
+            ]
+        }));
+        let history = ExchangeRatesVaultValue::from_vault(&vault).expect("Can read exchange rates");
+        let ex = ExchangeRates::from_history_and_rates(
+            history,
+            vec![("JPY".to_string(), dec!(100))],
+            &CurrencyTable::iso_4217(),
+        ).expect("Can create exchange rates");
+
+        let euros = ex.new_amount_at(&"EUR".to_string(), dec!(0), &date(2024, 2, 1)).unwrap();
+        let yens = ex.new_amount_at(&"JPY".to_string(), dec!(1000), &date(2024, 2, 1)).unwrap();
+        assert_eq!(euros.add(&yens).to_string(), "€10.00");
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::amounts::CurrencyIdent;
+use crate::error::ChoError;
+use crate::vault::{Vault, VaultReadable};
+
+/// Where the sign of a currency is written relative to the figure
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum SignPlacement {
+    #[default]
+    #[serde(rename = "before")]
+    Before,
+    #[serde(rename = "after")]
+    After,
+}
+
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct CurrencyDefinition {
+    pub code: CurrencyIdent,
+    pub sign: String,
+    /// Number of digits after the decimal separator. Figures are not rounded when this is None.
+    #[serde(default = "default_minor_units")]
+    pub minor_units: Option<u32>,
+    #[serde(default)]
+    pub sign_placement: SignPlacement,
+}
+
+fn default_minor_units() -> Option<u32> {
+    Some(2)
+}
+
+/// Currencies defined by the user in the vault. They take precedence over the ISO 4217 ones.
+pub type CustomCurrenciesVaultValue = Vec<CurrencyDefinition>;
+impl VaultReadable for CustomCurrenciesVaultValue {
+    const KEY: &'static str = "currencies";
+}
+
+#[derive(Clone, Debug)]
+pub struct CurrencyTable {
+    definitions: HashMap<CurrencyIdent, CurrencyDefinition>,
+}
+
+impl CurrencyTable {
+    pub fn iso_4217() -> CurrencyTable {
+        CurrencyTable {
+            definitions: ISO_4217
+                .iter()
+                .map(|(code, sign, minor_units)| {
+                    (code.to_string(), CurrencyDefinition {
+                        code: code.to_string(),
+                        sign: sign.unwrap_or(code).to_string(),
+                        minor_units: *minor_units,
+                        sign_placement: SignPlacement::Before,
+                    })
+                })
+                .collect()
+        }
+    }
+
+    pub fn with_custom_currencies(mut self, custom_currencies: CustomCurrenciesVaultValue) -> CurrencyTable {
+        for definition in custom_currencies {
+            self.definitions.insert(definition.code.clone(), definition);
+        }
+        self
+    }
+
+    pub fn from_vault<V: Vault>(vault: &V) -> Result<CurrencyTable, ChoError> {
+        let custom_currencies = CustomCurrenciesVaultValue::from_vault_optional(vault)?.unwrap_or_default();
+        Ok(CurrencyTable::iso_4217().with_custom_currencies(custom_currencies))
+    }
+
+    pub fn get(&self, code: &CurrencyIdent) -> Result<&CurrencyDefinition, ChoError> {
+        self.definitions.get(code).ok_or_else(|| ChoError::UnsupportedCurrency(code.clone()))
+    }
+}
+
+/// Active ISO 4217 codes: (code, sign, minor units).
+/// Signs follow the English CLDR symbols, the code itself is used as sign when CLDR has none.
+/// Minor units are None for the codes ISO 4217 marks as "N.A." (precious metals, special units).
+const ISO_4217: &[(&str, Option<&str>, Option<u32>)] = &[
+    ("AED", None, Some(2)),
+    ("AFN", None, Some(2)),
+    ("ALL", None, Some(2)),
+    ("AMD", None, Some(2)),
+    ("ANG", None, Some(2)),
+    ("AOA", None, Some(2)),
+    ("ARS", None, Some(2)),
+    ("AUD", Some("A$"), Some(2)),
+    ("AWG", None, Some(2)),
+    ("AZN", None, Some(2)),
+    ("BAM", None, Some(2)),
+    ("BBD", None, Some(2)),
+    ("BDT", None, Some(2)),
+    ("BGN", None, Some(2)),
+    ("BHD", None, Some(3)),
+    ("BIF", None, Some(0)),
+    ("BMD", None, Some(2)),
+    ("BND", None, Some(2)),
+    ("BOB", None, Some(2)),
+    ("BOV", None, Some(2)),
+    ("BRL", Some("R$"), Some(2)),
+    ("BSD", None, Some(2)),
+    ("BTN", None, Some(2)),
+    ("BWP", None, Some(2)),
+    ("BYN", None, Some(2)),
+    ("BZD", None, Some(2)),
+    ("CAD", Some("CA$"), Some(2)),
+    ("CDF", None, Some(2)),
+    ("CHE", None, Some(2)),
+    ("CHF", None, Some(2)),
+    ("CHW", None, Some(2)),
+    ("CLF", None, Some(4)),
+    ("CLP", None, Some(0)),
+    ("CNY", Some("CN¥"), Some(2)),
+    ("COP", None, Some(2)),
+    ("COU", None, Some(2)),
+    ("CRC", None, Some(2)),
+    ("CUP", None, Some(2)),
+    ("CVE", None, Some(2)),
+    ("CZK", None, Some(2)),
+    ("DJF", None, Some(0)),
+    ("DKK", None, Some(2)),
+    ("DOP", None, Some(2)),
+    ("DZD", None, Some(2)),
+    ("EGP", None, Some(2)),
+    ("ERN", None, Some(2)),
+    ("ETB", None, Some(2)),
+    ("EUR", Some("€"), Some(2)),
+    ("FJD", None, Some(2)),
+    ("FKP", None, Some(2)),
+    ("GBP", Some("£"), Some(2)),
+    ("GEL", None, Some(2)),
+    ("GHS", None, Some(2)),
+    ("GIP", None, Some(2)),
+    ("GMD", None, Some(2)),
+    ("GNF", None, Some(0)),
+    ("GTQ", None, Some(2)),
+    ("GYD", None, Some(2)),
+    ("HKD", Some("HK$"), Some(2)),
+    ("HNL", None, Some(2)),
+    ("HTG", None, Some(2)),
+    ("HUF", None, Some(2)),
+    ("IDR", None, Some(2)),
+    ("ILS", Some("₪"), Some(2)),
+    ("INR", Some("₹"), Some(2)),
+    ("IQD", None, Some(3)),
+    ("IRR", None, Some(2)),
+    ("ISK", None, Some(0)),
+    ("JMD", None, Some(2)),
+    ("JOD", None, Some(3)),
+    ("JPY", Some("¥"), Some(0)),
+    ("KES", None, Some(2)),
+    ("KGS", None, Some(2)),
+    ("KHR", None, Some(2)),
+    ("KMF", None, Some(0)),
+    ("KPW", None, Some(2)),
+    ("KRW", Some("₩"), Some(0)),
+    ("KWD", None, Some(3)),
+    ("KYD", None, Some(2)),
+    ("KZT", None, Some(2)),
+    ("LAK", None, Some(2)),
+    ("LBP", None, Some(2)),
+    ("LKR", None, Some(2)),
+    ("LRD", None, Some(2)),
+    ("LSL", None, Some(2)),
+    ("LYD", None, Some(3)),
+    ("MAD", None, Some(2)),
+    ("MDL", None, Some(2)),
+    ("MGA", None, Some(2)),
+    ("MKD", None, Some(2)),
+    ("MMK", None, Some(2)),
+    ("MNT", None, Some(2)),
+    ("MOP", None, Some(2)),
+    ("MRU", None, Some(2)),
+    ("MUR", None, Some(2)),
+    ("MVR", None, Some(2)),
+    ("MWK", None, Some(2)),
+    ("MXN", Some("MX$"), Some(2)),
+    ("MXV", None, Some(2)),
+    ("MYR", None, Some(2)),
+    ("MZN", None, Some(2)),
+    ("NAD", None, Some(2)),
+    ("NGN", None, Some(2)),
+    ("NIO", None, Some(2)),
+    ("NOK", None, Some(2)),
+    ("NPR", None, Some(2)),
+    ("NZD", Some("NZ$"), Some(2)),
+    ("OMR", None, Some(3)),
+    ("PAB", None, Some(2)),
+    ("PEN", None, Some(2)),
+    ("PGK", None, Some(2)),
+    ("PHP", Some("₱"), Some(2)),
+    ("PKR", None, Some(2)),
+    ("PLN", None, Some(2)),
+    ("PYG", None, Some(0)),
+    ("QAR", None, Some(2)),
+    ("RON", None, Some(2)),
+    ("RSD", None, Some(2)),
+    ("RUB", None, Some(2)),
+    ("RWF", None, Some(0)),
+    ("SAR", None, Some(2)),
+    ("SBD", None, Some(2)),
+    ("SCR", None, Some(2)),
+    ("SDG", None, Some(2)),
+    ("SEK", None, Some(2)),
+    ("SGD", None, Some(2)),
+    ("SHP", None, Some(2)),
+    ("SLE", None, Some(2)),
+    ("SOS", None, Some(2)),
+    ("SRD", None, Some(2)),
+    ("SSP", None, Some(2)),
+    ("STN", None, Some(2)),
+    ("SVC", None, Some(2)),
+    ("SYP", None, Some(2)),
+    ("SZL", None, Some(2)),
+    ("THB", None, Some(2)),
+    ("TJS", None, Some(2)),
+    ("TMT", None, Some(2)),
+    ("TND", None, Some(3)),
+    ("TOP", None, Some(2)),
+    ("TRY", None, Some(2)),
+    ("TTD", None, Some(2)),
+    ("TWD", Some("NT$"), Some(2)),
+    ("TZS", None, Some(2)),
+    ("UAH", None, Some(2)),
+    ("UGX", None, Some(0)),
+    ("USD", Some("$"), Some(2)),
+    ("USN", None, Some(2)),
+    ("UYI", None, Some(0)),
+    ("UYU", None, Some(2)),
+    ("UYW", None, Some(4)),
+    ("UZS", None, Some(2)),
+    ("VED", None, Some(2)),
+    ("VES", None, Some(2)),
+    ("VND", Some("₫"), Some(0)),
+    ("VUV", None, Some(0)),
+    ("WST", None, Some(2)),
+    ("XAF", Some("FCFA"), Some(0)),
+    ("XAG", None, None),
+    ("XAU", None, None),
+    ("XBA", None, None),
+    ("XBB", None, None),
+    ("XBC", None, None),
+    ("XBD", None, None),
+    ("XCD", Some("EC$"), Some(2)),
+    ("XCG", None, Some(2)),
+    ("XDR", None, None),
+    ("XOF", None, Some(0)),
+    ("XPD", None, None),
+    ("XPF", Some("CFPF"), Some(0)),
+    ("XPT", None, None),
+    ("XSU", None, None),
+    ("XTS", None, None),
+    ("XUA", None, None),
+    ("XXX", None, None),
+    ("YER", None, Some(2)),
+    ("ZAR", None, Some(2)),
+    ("ZMW", None, Some(2)),
+    ("ZWG", None, Some(2)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultImpl;
+    use serde_json::json;
+
+    #[test]
+    fn iso_4217_codes_are_unique_and_signs_are_unambiguous() {
+        let mut codes: Vec<&str> = ISO_4217.iter().map(|(code, _, _)| *code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), ISO_4217.len());
+
+        let table = CurrencyTable::iso_4217();
+        let mut signs: Vec<&String> = table.definitions.values().map(|definition| &definition.sign).collect();
+        signs.sort();
+        signs.dedup();
+        assert_eq!(signs.len(), ISO_4217.len());
+    }
+
+    #[test]
+    fn custom_currencies_from_vault() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "currencies": [
+                {"code": "BTC", "sign": "₿", "minor_units": 8},
+                {"code": "SEK", "sign": "kr", "sign_placement": "after"}
+            ]
+        }));
+
+        let table = CurrencyTable::from_vault(&vault).expect("Can read currency table");
+
+        assert_eq!(table.get(&"BTC".to_string()), Ok(&CurrencyDefinition {
+            code: "BTC".to_string(),
+            sign: "₿".to_string(),
+            minor_units: Some(8),
+            sign_placement: SignPlacement::Before,
+        }));
+        assert_eq!(table.get(&"SEK".to_string()), Ok(&CurrencyDefinition {
+            code: "SEK".to_string(),
+            sign: "kr".to_string(),
+            minor_units: Some(2),
+            sign_placement: SignPlacement::After,
+        }));
+        assert_eq!(table.get(&"JPY".to_string()).map(|definition| definition.minor_units), Ok(Some(0)));
+    }
+
+    #[test]
+    fn unknown_currency() {
+        assert!(CurrencyTable::iso_4217().get(&"ABC".to_string()).is_err());
+    }
+}
@@ -1,126 +1,188 @@
 use crate::amounts::exchange_rates::ExchangeRates;
-use crate::amounts::{Add, Amount, Div, Figure, Minus, RawAmount};
+use crate::error::{ChoError, Context};
+use crate::amounts::{Add, Amount, CurrencyIdent, Div, Figure, Minus, RawAmount};
 use crate::period::{
     ErrorPeriodsBetween, Period, PeriodConfigurationVaultValue, PeriodsConfiguration,
 };
 use crate::remaining_operation::core_types::{GroupBuilder, IllustrationValue, Operand, OperandBuilder};
-use crate::vault::VaultReadable;
+use crate::vault::{Vault, VaultReadable};
+use crate::chrono_stack::{ChronoStackSlice, ChronoStackVisitor, ChronoStackWalker};
 use chrono::format::parse;
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::de::{Error, Visitor};
-use serde::{Deserialize, Deserializer};
+use serde::Deserialize;
 use serde_json::value::Index;
-use std::fmt::{Formatter, Write};
-use std::str::{FromStr, Split};
+use std::fmt::{Display, Formatter, Write};
+use line_parser::{parse_file_line, parse_line, FileLine};
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
 
+pub mod checking;
+mod line_parser;
+pub mod recording;
+
+const BUCKET_DIR: &str = "buckets";
+const BUCKET_FILE_EXTENSION: &str = "bucket";
+
+/// Buckets are read from the "buckets" key of the configuration file and from the .bucket files
+/// of the buckets directory. Both sources are optional.
 pub type BucketsVaultValue = Vec<Bucket>;
 impl VaultReadable for BucketsVaultValue {
     const KEY: &'static str = "buckets";
+
+    fn from_vault<V: Vault>(vault: &V) -> Result<Self, ChoError> {
+        let mut buckets: BucketsVaultValue = vault
+            .read_optional_vault_values(Self::KEY.into())?
+            .unwrap_or_default();
+
+        for bucket in read_bucket_directory(vault)? {
+            if buckets.iter().any(|other_bucket| other_bucket.name == bucket.name) {
+                return Err(format!("Bucket \"{}\" is defined more than once", bucket.name).into());
+            }
+            buckets.push(bucket);
+        }
+
+        Ok(buckets)
+    }
+}
+
+// TODO - Like AccountGetter, this reads files directly instead of going through the Vault
+fn read_bucket_directory<V: Vault>(vault: &V) -> Result<Vec<Bucket>, ChoError> {
+    let directory = vault.path().join(BUCKET_DIR);
+    if !directory.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut paths = read_dir(&directory)
+        .and_then(|dir_reader| dir_reader
+            .map(|maybe_dir_entry| maybe_dir_entry.map(|dir_entry| dir_entry.path()))
+            .collect::<Result<Vec<PathBuf>, std::io::Error>>())
+        .map_err(|source| ChoError::File { path: directory.clone(), source })
+        .context(|| "Could not read the Buckets directory")?;
+    paths.retain(|path| path.is_file() && path.extension().is_some_and(|extension| extension == BUCKET_FILE_EXTENSION));
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let path_str = path.to_str().unwrap_or("(unable to get filename)");
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or(format!("Could not get the bucket name of file {}", path_str))?;
+            let content = read_to_string(path)
+                .map_err(|source| ChoError::File { path: path.clone(), source })
+                .context(|| "Could not read bucket file")?;
+            Bucket::from_bucket_file(name, &content).context(|| format!("Could not parse bucket file {}", path_str))
+        })
+        .collect()
 }
 
 impl GroupBuilder<Bucket> for BucketsVaultValue {
-    fn build(self) -> Result<(String, Vec<Bucket>), String> {
+    fn build(self) -> Result<(String, Vec<Bucket>), ChoError> {
         Ok(("Buckets".into(), self.into_iter().collect()))
     }
 }
 
-#[derive(Deserialize, Debug, Eq, PartialEq)]
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(try_from = "BucketJson")]
 pub struct Bucket {
     name: String,
+    /// Currency in which the bucket's figures are reported. Lines in other currencies are converted into it.
+    currency: CurrencyIdent,
     lines: Vec<Line>,
 }
 
+#[derive(Deserialize)]
+struct BucketJson {
+    name: String,
+    /// Buckets written before they had a currency were all in yen
+    #[serde(default = "default_bucket_currency")]
+    currency: CurrencyIdent,
+    lines: Vec<String>,
+}
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct Line((NaiveDate, Action));
+fn default_bucket_currency() -> CurrencyIdent {
+    "JPY".to_string()
+}
 
-impl<'de> Deserialize<'de> for Line {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct LineVisitor;
-        impl LineVisitor {
-            fn parse_amount<E: Error>(line: &mut Split<&str>) -> Result<RawAmount, E> {
-                let raw_amount_str = line.next().ok_or(Error::custom("No amounts specified"))?;
-
-                let mut raw_amount_str_itr = raw_amount_str.chars();
-                let sign = raw_amount_str_itr
-                    .next()
-                    .map(|sign| sign.to_string())
-                    .ok_or(Error::custom("amount is too short"))?;
-
-                let figure_raw: String = raw_amount_str_itr.collect();
-                let figure = Decimal::from_str_exact(&figure_raw).map_err(|err| {
-                    Error::custom(format!(
-                        "Error parsing amount: {}. Error: {}",
-                        figure_raw, err
-                    ))
-                })?;
-
-                Ok(RawAmount { sign, figure })
-            }
-        }
+impl TryFrom<BucketJson> for Bucket {
+    type Error = String;
+
+    fn try_from(bucket_json: BucketJson) -> Result<Self, Self::Error> {
+        let lines = bucket_json.lines
+            .iter()
+            .enumerate()
+            .map(|(index, raw_line)| parse_line(raw_line).map_err(|error| format!(
+                "Bucket \"{}\", line {}, {}", bucket_json.name, index + 1, error
+            )))
+            .collect::<Result<Vec<Option<Line>>, String>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Ok(Bucket {
+            name: bucket_json.name,
+            currency: bucket_json.currency,
+            lines,
+        })
+    }
+}
 
-        impl<'de> Visitor<'de> for LineVisitor {
-            type Value = Line;
-
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("a line")
-            }
-
-            fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
-                // TODO rewrite with nom
-                let mut line = v.split(" ");
-                let raw_date = line
-                    .next()
-                    .ok_or(Error::custom("Could not find the date"))?;
-                let date = NaiveDate::parse_from_str(raw_date, "%Y/%m/%d").map_err(|err| {
-                    Error::custom(format!(
-                        "Failed to parse date: {}. Error: {}",
-                        raw_date, err
-                    ))
-                })?;
-
-                let tag = line.next().ok_or(Error::custom("No tag specified"))?;
-                let line_data = match tag {
-                    "TARG" => {
-                        let raw_amount = LineVisitor::parse_amount(&mut line)?;
-
-                        let raw_target_date = line
-                            .next()
-                            .ok_or(Error::custom("No target date specified"))?;
-                        let target_date = NaiveDate::parse_from_str(raw_target_date, "%Y/%m/%d")
-                            .map_err(|err| {
-                                Error::custom(format!(
-                                    "Failed to parse date: {}. Error: {}",
-                                    raw_target_date, err
-                                ))
-                            })?;
-
-                        Ok(Action::SetTarget {
-                            amount: raw_amount,
-                            target_date,
-                        })
+impl Bucket {
+    /// Build a bucket from the content of a .bucket file. It holds one line per action and
+    /// declares the bucket's currency with a "CURRENCY <code>" line.
+    fn from_bucket_file(name: &str, content: &str) -> Result<Bucket, String> {
+        let mut currency: Option<CurrencyIdent> = None;
+        let mut lines = Vec::new();
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let file_line = parse_file_line(raw_line)
+                .map_err(|error| format!("Bucket \"{}\", line {}, {}", name, index + 1, error))?;
+            match file_line {
+                Some(FileLine::Currency(code)) => {
+                    if currency.is_some() {
+                        return Err(format!(
+                            "Bucket \"{}\", line {}: the currency is declared more than once", name, index + 1
+                        ));
                     }
-                    "DEPO" => Ok(Action::Deposit(LineVisitor::parse_amount(&mut line)?)),
-                    "DEPO-" => Ok(Action::DepositCancellation(LineVisitor::parse_amount(
-                        &mut line,
-                    )?)),
-                    "WITH" => Ok(Action::Withdrawal(LineVisitor::parse_amount(&mut line)?)),
-                    "WITH-" => Ok(Action::WithdrawalCancellation(LineVisitor::parse_amount(
-                        &mut line,
-                    )?)),
-                    _ => Err(Error::custom("Unknown tag")),
-                }?;
-
-                Ok(Line((date, line_data)))
+                    currency = Some(code);
+                }
+                Some(FileLine::Line(line)) => lines.push(line),
+                None => {}
             }
         }
 
-        deserializer.deserialize_str(LineVisitor)
+        Ok(Bucket {
+            name: name.to_string(),
+            currency: currency.ok_or(format!(
+                "Bucket \"{}\" does not declare its currency (add a \"CURRENCY <code>\" line)", name
+            ))?,
+            lines,
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct Line((NaiveDate, Action));
+
+/// The line as it is written in a bucket, eg. "2025/09/01 DEPO ¥150"
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Line((date, action)) = self;
+        let (tag, amount) = match action {
+            Action::Deposit(amount) => ("DEPO", amount),
+            Action::DepositCancellation(amount) => ("DEPO-", amount),
+            Action::Withdrawal(amount) => ("WITH", amount),
+            Action::WithdrawalCancellation(amount) => ("WITH-", amount),
+            Action::SetTarget { amount, .. } => ("TARG", amount),
+        };
+        write!(f, "{} {} {}{}", date.format("%Y/%m/%d"), tag, amount.sign, amount.figure)?;
+        if let Action::SetTarget { target_date, .. } = action {
+            write!(f, " {}", target_date.format("%Y/%m/%d"))?;
+        }
+        Ok(())
     }
 }
 
@@ -147,226 +209,223 @@ pub struct BucketAtDate {
     total: Amount,
 }
 
-impl Bucket {
-    // TODO make the buckets truly multi currency (replace any "JPY")
+/// The lines a sum of the bucket is made of
+#[derive(Clone, Copy, Debug)]
+enum Flow {
+    /// Everything that comes in and out of the bucket
+    Total,
+    Deposits,
+    Withdrawals,
+}
 
-    /* TODO Idea for a refactor
+/// How a line counts toward a flow
+enum Movement<'a> {
+    In(&'a RawAmount),
+    Out(&'a RawAmount),
+    /// Takes back what an earlier line put in the flow. The flow cannot go below zero because of it.
+    Cancellation(&'a RawAmount),
+}
 
-    ChronoStackWalker. It takes an implementation of the ChronoStackVisitor. It also takes a list of
-    CalendarEntry, a (date, T) tuple.
-    When executed, it calls visitor.visit(date, obj: T) for a slice of the CalendarEntry list.
+impl Flow {
+    fn movement<'a>(&self, action: &'a Action) -> Option<Movement<'a>> {
+        match (self, action) {
+            (Flow::Total | Flow::Deposits, Action::Deposit(amount)) => Some(Movement::In(amount)),
+            (Flow::Total | Flow::Deposits, Action::DepositCancellation(amount)) => Some(Movement::Cancellation(amount)),
+            (Flow::Total, Action::Withdrawal(amount)) => Some(Movement::Out(amount)),
+            (Flow::Total, Action::WithdrawalCancellation(amount)) => Some(Movement::In(amount)),
+            (Flow::Withdrawals, Action::Withdrawal(amount)) => Some(Movement::In(amount)),
+            (Flow::Withdrawals, Action::WithdrawalCancellation(amount)) => Some(Movement::Cancellation(amount)),
+            _ => None,
+        }
+    }
 
-    The slice is configurable:
-    - CalendarEntries in in a period
-    - CalendarEntries up until a period
-    - All calendar entries
+    fn cancellation_error(&self) -> &'static str {
+        match self {
+            Flow::Total | Flow::Deposits => "attempt to withdraw more money than the Bucket contains",
+            Flow::Withdrawals => "attempt to put back money that was not withdrawn",
+        }
+    }
+}
 
-    It verifies if entries are in order (over the whole stack - even if it won't call visitor on it)
+trait FlowVisitor {
+    fn visit(&mut self, date: &NaiveDate, movement: Movement) -> Result<(), ChoError>;
+}
 
-    Another object, the BucketChronoStackWalker is built upon the ChronoStack walker.
-    Features:
-    - it filters what type of lines are passed in
+/// Only hands the lines that count toward the flow to the visitor
+struct FlowFilter<'v, V: FlowVisitor> {
+    flow: Flow,
+    visitor: &'v mut V,
+}
 
-    BEFORE STARTING TO IMPLEMENT, DO NOT FORGET TO UNCOMMENT THE TESTS THAT ARE CURRENTLY COMMENTED OUT WAITING FOR THIS REFACTOR
-    */
+impl<V: FlowVisitor> ChronoStackVisitor<Action> for FlowFilter<'_, V> {
+    fn visit(&mut self, date: &NaiveDate, action: &Action) -> Result<(), ChoError> {
+        match self.flow.movement(action) {
+            Some(movement) => self.visitor.visit(date, movement),
+            None => Ok(()),
+        }
+    }
+}
 
+/// Sums the lines of a flow. The sum stays None until a line of the flow is visited.
+struct FlowSum<'a> {
+    bucket: &'a Bucket,
+    ex: &'a ExchangeRates,
+    /// Reported when a cancellation brings the sum below zero. Cancellations are not checked when None.
+    cancellation_error: Option<&'static str>,
+    sum: Option<Amount>,
+}
+
+impl FlowVisitor for FlowSum<'_> {
+    fn visit(&mut self, date: &NaiveDate, movement: Movement) -> Result<(), ChoError> {
+        let sum = match self.sum.take() {
+            Some(sum) => sum,
+            None => self.ex.zero(&self.bucket.currency)?,
+        };
+
+        let sum = match movement {
+            Movement::In(amount) => sum.add(&self.bucket.line_amount(self.ex, amount, date)?),
+            Movement::Out(amount) => sum.minus(&self.bucket.line_amount(self.ex, amount, date)?),
+            Movement::Cancellation(amount) => {
+                let sum = sum.minus(&self.bucket.line_amount(self.ex, amount, date)?);
+                match self.cancellation_error {
+                    Some(error) if sum.is_negative() => return Err(error.into()),
+                    _ => sum,
+                }
+            }
+        };
+
+        self.sum = Some(sum);
+        Ok(())
+    }
+}
+
+/// Keeps the last target set in the bucket
+struct TargetVisitor<'a> {
+    bucket: &'a Bucket,
+    ex: &'a ExchangeRates,
+    target: Option<(Amount, NaiveDate)>,
+}
+
+impl ChronoStackVisitor<Action> for TargetVisitor<'_> {
+    fn visit(&mut self, date: &NaiveDate, action: &Action) -> Result<(), ChoError> {
+        if let Action::SetTarget { amount, target_date } = action {
+            self.target = Some((self.bucket.line_amount(self.ex, amount, date)?, *target_date));
+        }
+        Ok(())
+    }
+}
+
+/// Walks the lines of a bucket, filtering them by the flow they count toward
+struct BucketChronoStackWalker<'a> {
+    bucket: &'a Bucket,
+    ex: &'a ExchangeRates,
+    walker: ChronoStackWalker<'a, Action>,
+}
+
+impl<'a> BucketChronoStackWalker<'a> {
+    fn new(bucket: &'a Bucket, ex: &'a ExchangeRates) -> Result<BucketChronoStackWalker<'a>, ChoError> {
+        Ok(BucketChronoStackWalker {
+            bucket,
+            ex,
+            walker: ChronoStackWalker::new(bucket.lines.iter().map(|Line(entry)| entry))?,
+        })
+    }
+
+    fn walk_flow(&self, slice: ChronoStackSlice, flow: Flow, visitor: &mut impl FlowVisitor) -> Result<(), ChoError> {
+        self.walker.walk(slice, &mut FlowFilter { flow, visitor })
+    }
+
+    fn sum(&self, slice: ChronoStackSlice, flow: Flow) -> Result<Option<Amount>, ChoError> {
+        let mut visitor = FlowSum { bucket: self.bucket, ex: self.ex, cancellation_error: None, sum: None };
+        self.walk_flow(slice, flow, &mut visitor)?;
+        Ok(visitor.sum)
+    }
+
+    fn sum_or_zero(&self, slice: ChronoStackSlice, flow: Flow) -> Result<Amount, ChoError> {
+        match self.sum(slice, flow)? {
+            Some(sum) => Ok(sum),
+            None => Ok(self.ex.zero(&self.bucket.currency)?),
+        }
+    }
+
+    /// Checks that no cancellation of the slice takes back more than was put in
+    fn check_cancellations(&self, slice: ChronoStackSlice) -> Result<(), ChoError> {
+        [Flow::Total, Flow::Withdrawals].iter().try_for_each(|flow| {
+            let mut visitor = FlowSum {
+                bucket: self.bucket,
+                ex: self.ex,
+                cancellation_error: Some(flow.cancellation_error()),
+                sum: None,
+            };
+            self.walk_flow(slice, *flow, &mut visitor)
+        })
+    }
+
+    fn target(&self, slice: ChronoStackSlice) -> Result<Option<(Amount, NaiveDate)>, ChoError> {
+        let mut visitor = TargetVisitor { bucket: self.bucket, ex: self.ex, target: None };
+        self.walker.walk(slice, &mut visitor)?;
+        Ok(visitor.target)
+    }
+}
+
+impl Bucket {
+    /// The amount of a line, converted into the bucket's currency at the rate in effect on the line's date
+    fn line_amount(&self, ex: &ExchangeRates, amount: &RawAmount, date: &NaiveDate) -> Result<Amount, ChoError> {
+        ex.convert_at(&ex.new_amount_from_raw_amount_at(amount, date)?, &self.currency, date)
+    }
+
+    /// The state of the bucket on the date. The last target of the bucket is used, even when it is set after the date.
     fn for_period(
         &self,
         period_config: &PeriodConfigurationVaultValue,
         date: &NaiveDate,
         ex: &ExchangeRates,
-    ) -> Result<BucketAtDate, String> {
-        let total = self.lines.iter().try_fold(
-            ex.zero(&"JPY".to_string())?,
-            |acc, Line((line_date, action))| {
-                if line_date <= date {
-                    match action {
-                        Action::Deposit(amount) | Action::WithdrawalCancellation(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.add(&parsed_amount)),
-                        Action::Withdrawal(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.minus(&parsed_amount)),
-                        Action::DepositCancellation(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.minus(&parsed_amount))
-                            .and_then(|new_acc| {
-                                if new_acc.is_negative() {
-                                    Err("attempt to withdraw more money than the Bucket contains"
-                                        .to_string())
-                                } else {
-                                    Ok(new_acc)
-                                }
-                            }),
-                        _ => Ok(acc),
-                    }
-                } else {
-                    Ok(acc)
-                }
-            },
-        )?;
-
-        let deposited = self.lines.iter().try_fold(
-            ex.zero(&"JPY".to_string())?,
-            |acc, Line((line_date, action))| {
-                if line_date <= date {
-                    match action {
-                        Action::Deposit(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.add(&parsed_amount)),
-                        Action::DepositCancellation(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.minus(&parsed_amount)),
-                        _ => Ok(acc),
-                    }
-                } else {
-                    Ok(acc)
-                }
-            },
-        )?;
-
-        let withdrawn = self.lines.iter().try_fold(
-            ex.zero(&"JPY".to_string())?,
-            |acc, Line((line_date, action))| {
-                if line_date <= date {
-                    match action {
-                        Action::Withdrawal(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.add(&parsed_amount)),
-                        Action::WithdrawalCancellation(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.minus(&parsed_amount))
-                            .and_then(|new_acc| {
-                                if new_acc.is_negative() {
-                                    Err("attempt to put back money that was not withdrawn"
-                                        .to_string())
-                                } else {
-                                    Ok(new_acc)
-                                }
-                            }),
-                        _ => Ok(acc),
-                    }
-                } else {
-                    Ok(acc)
-                }
-            },
-        )?;
+    ) -> Result<BucketAtDate, ChoError> {
+        self.for_period_with_targets(period_config, date, ex, ChronoStackSlice::All)
+    }
+
+    /// The state of the bucket on the date, using the last target set within `targets`
+    fn for_period_with_targets(
+        &self,
+        period_config: &PeriodConfigurationVaultValue,
+        date: &NaiveDate,
+        ex: &ExchangeRates,
+        targets: ChronoStackSlice,
+    ) -> Result<BucketAtDate, ChoError> {
+        let walker = BucketChronoStackWalker::new(self, ex)?;
+        let until_today = ChronoStackSlice::UntilDate(date);
+        // Lines after the date have not happened yet, they are only checked once it has come
+        walker.check_cancellations(until_today)?;
+
+        let total = walker.sum_or_zero(until_today, Flow::Total)?;
+        let deposited = walker.sum_or_zero(until_today, Flow::Deposits)?;
+        let withdrawn = walker.sum_or_zero(until_today, Flow::Withdrawals)?;
 
         let current_period = period_config.period_for_date(date)?;
+        let this_period_until_today = Period {
+            start_date: current_period.start_date,
+            end_date: *date,
+        };
 
-        let deposited_until_period_start = self.lines.iter().try_fold(
-            ex.zero(&"JPY".to_string())?,
-            |acc, Line((line_date, action))| {
-                if line_date < &current_period.start_date {
-                    match action {
-                        // Withdrawals should never count toward what was deposited
-                        Action::Deposit(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.add(&parsed_amount)),
-                        Action::DepositCancellation(amount) => ex
-                            .new_amount_from_raw_amount(amount)
-                            .map(|parsed_amount| acc.minus(&parsed_amount)),
-                        _ => Ok(acc),
-                    }
-                } else {
-                    Ok(acc)
-                }
-            },
-        )?;
-
-        let total_this_period =
-            self.lines
-                .iter()
-                .try_fold(None, |acc, Line((line_date, action))| {
-                    if line_date >= &current_period.start_date && line_date <= date {
-                        match action {
-                            Action::Deposit(amount) | Action::WithdrawalCancellation(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.add(&parsed_amount)))
-                            }
-                            Action::DepositCancellation(amount) | Action::Withdrawal(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.minus(&parsed_amount)))
-                            }
-                            _ => Ok(acc),
-                        }
-                    } else {
-                        Ok(acc)
-                    }
-                })?;
-
-        let deposited_this_period =
-            self.lines
-                .iter()
-                .try_fold(None, |acc, Line((line_date, action))| {
-                    if line_date >= &current_period.start_date && line_date <= date {
-                        match action {
-                            Action::Deposit(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.add(&parsed_amount)))
-                            }
-                            Action::DepositCancellation(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.minus(&parsed_amount)))
-                            }
-                            _ => Ok(acc),
-                        }
-                    } else {
-                        Ok(acc)
-                    }
-                })?;
-
-        let withdrawned_this_period =
-            self.lines
-                .iter()
-                .try_fold(None, |acc, Line((line_date, action))| {
-                    if line_date >= &current_period.start_date && line_date <= date {
-                        match action {
-                            Action::Withdrawal(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.add(&parsed_amount)))
-                            }
-                            Action::WithdrawalCancellation(amount) => {
-                                let acc = acc.unwrap_or(ex.zero(&"JPY".to_string())?);
-                                ex.new_amount_from_raw_amount(amount)
-                                    .map(|parsed_amount| Some(acc.minus(&parsed_amount)))
-                            }
-                            _ => Ok(acc),
-                        }
-                    } else {
-                        Ok(acc)
-                    }
-                })?;
+        // Withdrawals should never count toward what was deposited
+        let deposited_until_period_start =
+            walker.sum_or_zero(ChronoStackSlice::UntilPeriod(&current_period), Flow::Deposits)?;
 
-        let target: Option<(Amount, &NaiveDate)> = self
-            .lines
-            .iter()
-            .try_fold(None, |acc, Line((_, line))| match line {
-                Action::SetTarget {
-                    amount,
-                    target_date,
-                } => Ok::<std::option::Option<(Amount, &chrono::NaiveDate)>, String>(Some((
-                    ex.new_amount_from_raw_amount(&amount)?,
-                    target_date
-                ))),
-                _ => Ok(acc)
-            })?;
-
-        let recommended_deposit_figure = if let Some((target_amount, target_date)) = target {
-            let number_of_periods = match period_config.periods_between(date, target_date) {
+        let this_period = ChronoStackSlice::InPeriod(&this_period_until_today);
+        let total_this_period = walker.sum(this_period, Flow::Total)?;
+        let deposited_this_period = walker.sum(this_period, Flow::Deposits)?;
+        let withdrawned_this_period = walker.sum(this_period, Flow::Withdrawals)?;
+
+        let recommended_deposit_figure = if let Some((target_amount, target_date)) = walker.target(targets)? {
+            let number_of_periods = match period_config.periods_between(date, &target_date) {
                 Ok(nb) => nb,
                 Err(ErrorPeriodsBetween::EndBeforeStart) => 1,
-                any => any?,
+                Err(error) => return Err(String::from(error).into()),
             };
 
             let recommended_deposit_figure = Amount::maximum(
                 &target_amount.minus(&deposited_until_period_start),
-                &ex.zero(&"JPY".to_string())?,
+                &ex.zero(&self.currency)?,
             ).div(&Decimal::from(number_of_periods));
 
             Some(recommended_deposit_figure)
@@ -381,7 +440,7 @@ impl Bucket {
                 .unwrap_or(
                     recommended_deposit_figure
                         .clone()
-                        .unwrap_or(ex.zero(&"JPY".to_string())?)),
+                        .unwrap_or(ex.zero(&self.currency)?)),
             current_recommended_deposit: recommended_deposit_figure,
             current_actual_deposit: deposited_this_period,
             current_withdrawal: withdrawned_this_period,
@@ -392,13 +451,137 @@ impl Bucket {
     }
 }
 
+/// What happened to a bucket during a period
+#[derive(Debug, Eq, PartialEq)]
+pub struct BucketHistoryRow {
+    pub period: Period,
+    pub recommended_deposit: Option<Amount>,
+    pub actual_deposit: Option<Amount>,
+    pub withdrawal: Option<Amount>,
+    /// What the bucket contains at the end of the period
+    pub total: Amount,
+}
+
+impl Bucket {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// One row per period, from the period of the bucket's first line to the one containing today.
+    /// Each period only knows about the targets set before its end, and the current period stops at today.
+    pub fn history(
+        &self,
+        period_config: &PeriodConfigurationVaultValue,
+        today: &NaiveDate,
+        ex: &ExchangeRates,
+    ) -> Result<Vec<BucketHistoryRow>, ChoError> {
+        let Some(Line((first_date, _))) = self.lines.first() else {
+            return Ok(vec![]);
+        };
+
+        let mut rows = vec![];
+        let mut period = period_config.period_for_date(first_date)?;
+        while period.start_date <= *today {
+            let date = period.end_date.min(*today);
+            let bucket_at_date =
+                self.for_period_with_targets(period_config, &date, ex, ChronoStackSlice::UntilDate(&date))?;
+
+            let next_period_start = period
+                .end_date
+                .succ_opt()
+                .ok_or("Could not compute the start of the next period")?;
+            rows.push(BucketHistoryRow {
+                period,
+                recommended_deposit: bucket_at_date.current_recommended_deposit,
+                actual_deposit: bucket_at_date.current_actual_deposit,
+                withdrawal: bucket_at_date.current_withdrawal,
+                total: bucket_at_date.total,
+            });
+            period = period_config.period_for_date(&next_period_start)?;
+        }
+
+        Ok(rows)
+    }
+}
+
+impl Bucket {
+    /// The date by which the last target set in the bucket must be reached
+    fn target_date(&self) -> Option<NaiveDate> {
+        self.lines.iter().rev().find_map(|Line((_, action))| match action {
+            Action::SetTarget { target_date, .. } => Some(*target_date),
+            _ => None,
+        })
+    }
+
+    /// The deposit recommended for each period, from the current one to the one of the target date.
+    /// Every recommended deposit is assumed to be made, deposits already made this period count toward it.
+    pub fn projected_recommended_deposits(
+        &self,
+        period_config: &PeriodConfigurationVaultValue,
+        today: &NaiveDate,
+        ex: &ExchangeRates,
+    ) -> Result<Vec<(Period, Amount)>, ChoError> {
+        let Some(target_date) = self.target_date() else {
+            return Ok(vec![]);
+        };
+
+        let zero = ex.zero(&self.currency)?;
+        let mut projection = self.clone();
+        let mut deposits = vec![];
+        let mut date = *today;
+        let mut period = period_config.period_for_date(today)?;
+        while period.start_date <= target_date {
+            let bucket_at_date = projection.for_period(period_config, &date, ex)?;
+            let recommended_deposit = bucket_at_date.current_recommended_deposit.clone().unwrap_or(zero.clone());
+            let missing_deposit = bucket_at_date.missing_deposit(&zero);
+
+            let position = projection.lines.partition_point(|Line((line_date, _))| line_date <= &date);
+            projection
+                .lines
+                .insert(position, Line((date, Action::Deposit(missing_deposit.to_raw_amount()))));
+
+            date = period
+                .end_date
+                .succ_opt()
+                .ok_or("Could not compute the start of the next period")?;
+            deposits.push((period, recommended_deposit));
+            period = period_config.period_for_date(&date)?;
+        }
+
+        Ok(deposits)
+    }
+
+    /// What the bucket will contain at the end of the current period, once the deposit still recommended
+    /// this period is made
+    pub fn earmarked_at_period_end(
+        &self,
+        period_config: &PeriodConfigurationVaultValue,
+        today: &NaiveDate,
+        ex: &ExchangeRates,
+    ) -> Result<Amount, ChoError> {
+        let bucket_at_date = self.for_period(period_config, today, ex)?;
+        Ok(bucket_at_date.total.add(&bucket_at_date.missing_deposit(&ex.zero(&self.currency)?)))
+    }
+}
+
+impl BucketAtDate {
+    /// The part of the recommended deposit that was not made yet this period
+    fn missing_deposit(&self, zero: &Amount) -> Amount {
+        let recommended_deposit = self.current_recommended_deposit.clone().unwrap_or(zero.clone());
+        Amount::maximum(
+            &recommended_deposit.minus(&self.current_actual_deposit.clone().unwrap_or(zero.clone())),
+            zero,
+        )
+    }
+}
+
 impl OperandBuilder for Bucket {
     fn build(
         self,
         period_configuration: &PeriodConfigurationVaultValue,
         today: &NaiveDate,
         exchange_rates: &ExchangeRates,
-    ) -> Result<Option<Operand>, String> {
+    ) -> Result<Option<Operand>, ChoError> {
         let period = self.for_period(period_configuration, today, exchange_rates)?;
         Ok(Some(Operand {
             name: self.name,
@@ -440,12 +623,13 @@ mod test {
         NaiveDate::from_ymd_opt(2025, month, date).expect("Can create date")
     }
 
-    type TestResult = Result<BucketAtDate, String>;
+    type TestResult = Result<BucketAtDate, ChoError>;
     type ExpectedFn = Box<dyn Fn(&ExchangeRates) -> TestResult>;
 
     struct Test {
         executed: bool,
         lines: Vec<Line>,
+        today: NaiveDate,
         expected: ExpectedFn,
     }
 
@@ -454,14 +638,17 @@ mod test {
             Test {
                 executed: false,
                 lines: Vec::new(),
-                expected: Box::new(|_| Err("Please setup the test".to_string())),
+                today: mkdate(9, 15),
+                expected: Box::new(|_| Err("Please setup the test".into())),
             }
         }
     }
 
     impl Test {
+        /// Lines are kept in chronological order, whatever the order in which the test adds them
         pub fn add_line(mut self, date: NaiveDate, line: Action) -> Self {
-            self.lines.push(Line((date, line)));
+            let position = self.lines.partition_point(|Line((line_date, _))| line_date <= &date);
+            self.lines.insert(position, Line((date, line)));
             self
         }
 
@@ -495,9 +682,14 @@ mod test {
             )
         }
 
+        pub fn on(mut self, today: NaiveDate) -> Self {
+            self.today = today;
+            self
+        }
+
         pub fn expect_error(mut self, error: &str) -> Self {
             let error_string = error.to_string();
-            self.expected = Box::new(move |_| Err(error_string.clone()));
+            self.expected = Box::new(move |_| Err(error_string.clone().into()));
             self
         }
 
@@ -554,10 +746,11 @@ mod test {
             let ex = ExchangeRates::for_tests();
             let period_configuration =
                 PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {});
-            let today = mkdate(9, 15);
+            let today = self.today;
 
             let bucket = Bucket {
                 name: "test bucket inner".to_string(),
+                currency: "JPY".to_string(),
                 lines: self.lines.clone(),
             };
 
@@ -673,8 +866,8 @@ mod test {
                     },
                 )
                 .expect_bucket(|ex| BucketAtDate {
-                    recommended_or_actual_change: ex.yen("33333.33"),
-                    current_recommended_deposit: Some(ex.yen("33333.33")),
+                    recommended_or_actual_change: ex.yen("33333"),
+                    current_recommended_deposit: Some(ex.yen("33333")),
                     current_actual_deposit: None,
                     current_withdrawal: None,
                     total_deposit: ex.yen("0"),
@@ -702,8 +895,8 @@ mod test {
                     },
                 )
                 .expect_bucket(|ex| BucketAtDate {
-                    recommended_or_actual_change: ex.yen("33333.33"),
-                    current_recommended_deposit: Some(ex.yen("33333.33")),
+                    recommended_or_actual_change: ex.yen("33333"),
+                    current_recommended_deposit: Some(ex.yen("33333")),
                     current_actual_deposit: None,
                     current_withdrawal: None,
                     total_deposit: ex.yen("0"),
@@ -1437,19 +1630,26 @@ mod test {
                     .expect_bucket_recommended_commit_one_hundred_thousand_in_four_months()
                     .execute();
             }
-            /*
+
             #[test]
             fn one_cancellation_too_big_followed_by_one_deposit_that_brings_back_the_bucket_to_positive() {
+                // The lines have not happened yet, the cancellation is only checked once its date has come
+                Test::default()
+                    .target_set_in_current_period_one_hundred_thousand_in_four_months()
+                    .add_line(mkdate(10, 8), Action::Deposit(RawAmount::yen("25000")))
+                    .add_line(mkdate(10, 13), Action::DepositCancellation(RawAmount::yen("30000")))
+                    .add_line(mkdate(10, 15), Action::Deposit(RawAmount::yen("30000")))
+                    .expect_bucket_no_commits_one_hundred_thousand_in_four_months()
+                    .execute();
                 Test::default()
                     .target_set_in_current_period_one_hundred_thousand_in_four_months()
-                    .add_line(mkdate(10, 8), Line::Deposit(RawAmount::yen("25000")))
-                    .add_line(mkdate(10, 13), Line::DepositCancellation(RawAmount::yen("30000")))
-                    .add_line(mkdate(10, 15), Line::Deposit(RawAmount::yen("30000")))
+                    .add_line(mkdate(10, 8), Action::Deposit(RawAmount::yen("25000")))
+                    .add_line(mkdate(10, 13), Action::DepositCancellation(RawAmount::yen("30000")))
+                    .add_line(mkdate(10, 15), Action::Deposit(RawAmount::yen("30000")))
+                    .on(mkdate(10, 13))
                     .expect_error("attempt to withdraw more money than the Bucket contains")
                     .execute();
             }
-
-             */
         }
 
         mod across_periods {
@@ -1874,19 +2074,19 @@ mod test {
                     .expect_bucket_recommended_commit_one_hundred_thousand_in_four_months()
                     .execute();
             }
-            /*
+
             #[test]
-            fn one_cancellation_too_big_followed_by_one_deposit_that_brings_back_the_bucket_to_positive() {
+            fn one_withdrawal_too_big_followed_by_one_deposit_that_brings_back_the_bucket_to_positive() {
+                // Only cancellations are checked against what the bucket contains, not withdrawals: like in the current
+                // period, withdrawing more and depositing it back right after is valid
                 Test::default()
                     .target_set_in_current_period_one_hundred_thousand_in_four_months()
-                    .add_line(mkdate(10, 8), Line::Deposit(RawAmount::yen("25000")))
-                    .add_line(mkdate(10, 13), Line::Withdrawal(RawAmount::yen("30000")))
-                    .add_line(mkdate(10, 15), Line::Deposit(RawAmount::yen("30000")))
-                    .expect_error("attempt to withdraw more money than the Bucket contains")
+                    .add_line(mkdate(10, 8), Action::Deposit(RawAmount::yen("25000")))
+                    .add_line(mkdate(10, 13), Action::Withdrawal(RawAmount::yen("30000")))
+                    .add_line(mkdate(10, 15), Action::Deposit(RawAmount::yen("30000")))
+                    .expect_bucket_no_commits_one_hundred_thousand_in_four_months()
                     .execute();
             }
-
-             */
         }
 
         mod across_periods {
@@ -2379,6 +2579,87 @@ mod test {
         }
     }
 
+    #[test]
+    fn lines_out_of_chronological_order() {
+        let bucket = Bucket {
+            name: "test bucket inner".to_string(),
+            currency: "JPY".to_string(),
+            lines: vec![
+                Line((mkdate(9, 10), Action::Deposit(RawAmount::yen("25000")))),
+                Line((mkdate(9, 8), Action::Withdrawal(RawAmount::yen("5000")))),
+            ],
+        };
+
+        assert_eq!(
+            bucket.for_period(
+                &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+                &mkdate(9, 15),
+                &ExchangeRates::for_tests(),
+            ),
+            Err("entries are not in chronological order: 2025-09-08 comes after 2025-09-10".into())
+        );
+    }
+
+    #[test]
+    fn history() {
+        let ex = ExchangeRates::for_tests();
+        let bucket = Bucket {
+            name: "test bucket inner".to_string(),
+            currency: "JPY".to_string(),
+            lines: vec![
+                Line((mkdate(6, 20), Action::Deposit(RawAmount::yen("3000")))),
+                Line((mkdate(7, 1), Action::SetTarget { amount: RawAmount::yen("90000"), target_date: mkdate(9, 30) })),
+                Line((mkdate(7, 10), Action::Deposit(RawAmount::yen("30000")))),
+                Line((mkdate(8, 5), Action::Deposit(RawAmount::yen("10000")))),
+                Line((mkdate(8, 20), Action::Withdrawal(RawAmount::yen("5000")))),
+                Line((mkdate(9, 3), Action::Deposit(RawAmount::yen("20000")))),
+                Line((mkdate(9, 20), Action::Deposit(RawAmount::yen("20000")))),
+            ],
+        };
+        let period = |month: u32, last_day: u32| Period { start_date: mkdate(month, 1), end_date: mkdate(month, last_day) };
+
+        assert_eq!(
+            bucket.history(
+                &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+                &mkdate(9, 15),
+                &ex,
+            ),
+            Ok(vec![
+                // The target is not set yet
+                BucketHistoryRow {
+                    period: period(6, 30),
+                    recommended_deposit: None,
+                    actual_deposit: Some(ex.yen("3000")),
+                    withdrawal: None,
+                    total: ex.yen("3000"),
+                },
+                BucketHistoryRow {
+                    period: period(7, 31),
+                    recommended_deposit: Some(ex.yen("29000")),
+                    actual_deposit: Some(ex.yen("30000")),
+                    withdrawal: None,
+                    total: ex.yen("33000"),
+                },
+                // Fell behind schedule by ¥18500
+                BucketHistoryRow {
+                    period: period(8, 31),
+                    recommended_deposit: Some(ex.yen("28500")),
+                    actual_deposit: Some(ex.yen("10000")),
+                    withdrawal: Some(ex.yen("5000")),
+                    total: ex.yen("38000"),
+                },
+                // Stops at today
+                BucketHistoryRow {
+                    period: period(9, 30),
+                    recommended_deposit: Some(ex.yen("47000")),
+                    actual_deposit: Some(ex.yen("20000")),
+                    withdrawal: None,
+                    total: ex.yen("58000"),
+                },
+            ])
+        );
+    }
+
     #[test]
     fn create_operand() -> () {
         let ex = ExchangeRates::for_tests();
@@ -2388,6 +2669,7 @@ mod test {
 
         let bucket = Bucket {
             name: "test-bucket".to_string(),
+            currency: "JPY".to_string(),
             lines: vec![
                 Line((
                     mkdate(8, 13),
@@ -2450,6 +2732,7 @@ mod test {
 
         let bucket = Bucket {
             name: "test-bucket".to_string(),
+            currency: "JPY".to_string(),
             lines: vec![
                 Line((
                     mkdate(8, 13),
@@ -2507,6 +2790,7 @@ mod test {
 
             let bucket = Bucket {
                 name: "test-bucket".to_string(),
+                currency: "JPY".to_string(),
                 lines: vec![
                     Line((mkdate(8, 13), Action::Deposit(RawAmount::yen("1100")))),
                     Line((mkdate(8, 20), Action::Withdrawal(RawAmount::yen("500")))),
@@ -2554,6 +2838,74 @@ mod test {
         }
     }
 
+    #[test]
+    fn create_operand_foreign_currency_lines() {
+        let ex = ExchangeRates::from_history_and_rates(
+            serde_json::from_value(json!([
+                {"date": "2025-01-01", "rates": {"EUR": "1", "JPY": "2"}},
+                {"date": "2025-09-01", "rates": {"JPY": "4"}}
+            ])).expect("Can deserialize exchange rates"),
+            vec![],
+            &crate::amounts::currency_table::CurrencyTable::iso_4217(),
+        ).expect("Can create exchange rates");
+        let euro = |figure: &str| ex.new_amount(&"EUR".to_string(), Decimal::from_str_exact(figure).unwrap()).unwrap();
+        let period_configuration =
+            PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {});
+        let today = mkdate(9, 15);
+
+        let bucket = Bucket {
+            name: "test-bucket".to_string(),
+            currency: "EUR".to_string(),
+            lines: vec![
+                Line((
+                    mkdate(8, 1),
+                    Action::SetTarget {
+                        amount: RawAmount { sign: "€".to_string(), figure: dec!(100) },
+                        target_date: mkdate(10, 31),
+                    },
+                )),
+                // Converted at the rate of August: ¥2 for €1
+                Line((mkdate(8, 10), Action::Deposit(RawAmount::yen("40")))),
+                // Converted at the rate of September: ¥4 for €1
+                Line((mkdate(9, 10), Action::Deposit(RawAmount::yen("40")))),
+            ],
+        };
+
+        assert_eq!(
+            bucket.build(&period_configuration, &today, &ex),
+            Ok(Some(Operand {
+                name: "test-bucket".to_string(),
+                amount: euro("-10"),
+                illustration: vec![
+                    (
+                        "This period - recommended deposit".to_string(),
+                        IllustrationValue::Amount(euro("40"))
+                    ),
+                    (
+                        "This period - actual deposit".to_string(),
+                        IllustrationValue::Amount(euro("10"))
+                    ),
+                    (
+                        "This period - actual withdrawal".to_string(),
+                        IllustrationValue::NullAmount
+                    ),
+                    (
+                        "Deposited".to_string(),
+                        IllustrationValue::Amount(euro("30"))
+                    ),
+                    (
+                        "Withdrawn".to_string(),
+                        IllustrationValue::Amount(euro("0"))
+                    ),
+                    (
+                        "Total".to_string(),
+                        IllustrationValue::Amount(euro("30"))
+                    )
+                ]
+            }))
+        );
+    }
+
     mod vault_value_parser {
         use super::*;
         use pretty_assertions::assert_eq;
@@ -2565,6 +2917,7 @@ mod test {
             let (_dir, vault) = VaultImpl::create_mocked_vault(json!({"buckets": [
                 {
                     "name": "test-bucket",
+                    "currency": "JPY",
                     "lines": [
                         "2025/08/13 TARG ¥3000 2025/10/30",
                         "2025/08/13 DEPO ¥1100 #Comment",
@@ -2580,6 +2933,7 @@ mod test {
                 BucketsVaultValue::from_vault(&vault),
                 Ok(vec![Bucket {
                     name: "test-bucket".to_string(),
+                    currency: "JPY".to_string(),
                     lines: vec![
                         Line((
                             mkdate(8, 13),
@@ -2603,5 +2957,110 @@ mod test {
                 }])
             );
         }
+
+        #[test]
+        fn without_currency() {
+            let (_dir, vault) = VaultImpl::create_mocked_vault(json!({"buckets": [
+                {"name": "test-bucket", "lines": ["2025/08/13 DEPO ¥1100"]}
+            ]}));
+
+            assert_eq!(
+                BucketsVaultValue::from_vault(&vault),
+                Ok(vec![Bucket {
+                    name: "test-bucket".to_string(),
+                    currency: "JPY".to_string(),
+                    lines: vec![Line((mkdate(8, 13), Action::Deposit(RawAmount::yen("1100"))))]
+                }])
+            );
+        }
+
+        #[test]
+        fn parse_error_names_bucket_line_and_column() {
+            let (_dir, vault) = VaultImpl::create_mocked_vault(json!({"buckets": [
+                {
+                    "name": "holidays",
+                    "currency": "JPY",
+                    "lines": [
+                        "2025/08/13 TARG ¥3000 2025/10/30",
+                        "; Summer",
+                        "2025/08/20 WITHDRAW ¥500"
+                    ]
+                }
+            ]}));
+
+            let error = BucketsVaultValue::from_vault(&vault).unwrap_err();
+            assert!(
+                error.to_string().contains("Bucket \"holidays\", line 3, column 12: expected a tag (TARG, DEPO, DEPO-, WITH or WITH-), found \"WITHDRAW\""),
+                "{}",
+                error
+            );
+        }
+
+        fn create_bucket_file(vault: &VaultImpl, file_name: &str, content: &str) {
+            let directory = vault.path().join(BUCKET_DIR);
+            std::fs::create_dir_all(&directory).unwrap();
+            std::fs::write(directory.join(file_name), content).unwrap();
+        }
+
+        #[test]
+        fn bucket_directory() {
+            let (_dir, vault) = VaultImpl::create_mocked_vault(json!({"buckets": [
+                {
+                    "name": "from-config",
+                    "currency": "EUR",
+                    "lines": ["2025/08/13 DEPO €10"]
+                }
+            ]}));
+            create_bucket_file(&vault, "holidays.bucket", "; Summer holidays\nCURRENCY JPY\n\n2025/08/13  TARG ¥3000 2025/10/30\n2025/08/20\tDEPO ¥1,000 ; bonus\n");
+            create_bucket_file(&vault, "notes.txt", "Not a bucket");
+
+            assert_eq!(
+                BucketsVaultValue::from_vault(&vault),
+                Ok(vec![
+                    Bucket {
+                        name: "from-config".to_string(),
+                        currency: "EUR".to_string(),
+                        lines: vec![
+                            Line((mkdate(8, 13), Action::Deposit(RawAmount { sign: "€".to_string(), figure: dec!(10) }))),
+                        ]
+                    },
+                    Bucket {
+                        name: "holidays".to_string(),
+                        currency: "JPY".to_string(),
+                        lines: vec![
+                            Line((
+                                mkdate(8, 13),
+                                Action::SetTarget {
+                                    amount: RawAmount::yen("3000"),
+                                    target_date: mkdate(10, 30)
+                                }
+                            )),
+                            Line((mkdate(8, 20), Action::Deposit(RawAmount::yen("1000")))),
+                        ]
+                    },
+                ])
+            );
+        }
+
+        #[test]
+        fn bucket_directory_errors() {
+            let (_dir, vault) = VaultImpl::create_mocked_vault(json!({"buckets": [
+                {"name": "holidays", "currency": "EUR", "lines": []}
+            ]}));
+            create_bucket_file(&vault, "holidays.bucket", "CURRENCY JPY\n");
+            assert_eq!(
+                BucketsVaultValue::from_vault(&vault),
+                Err(ChoError::Other("Bucket \"holidays\" is defined more than once".to_string()))
+            );
+
+            assert_eq!(
+                Bucket::from_bucket_file("car", "2025/08/20 DEPO ¥1000\n"),
+                Err("Bucket \"car\" does not declare its currency (add a \"CURRENCY <code>\" line)".to_string())
+            );
+            assert_eq!(
+                Bucket::from_bucket_file("car", "CURRENCY JPY\n2025/08/20 DEPO 1000\n"),
+                Err("Bucket \"car\", line 2, column 17: expected a currency sign, found \"1000\"".to_string())
+            );
+        }
     }
 }
@@ -0,0 +1,268 @@
+use super::line_parser::{parse_file_line, parse_line, FileLine};
+use super::{Action, Bucket, BucketChronoStackWalker, BucketJson, BucketsVaultValue, Line, BUCKET_DIR, BUCKET_FILE_EXTENSION};
+use crate::chrono_stack::ChronoStackSlice;
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::{CurrencyIdent, RawAmount};
+use crate::check::{vault_file_name, Problem, CONFIGURATION_FILE};
+use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::vault::{Vault, VaultReadable};
+use serde_json::Value;
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+
+/// Where a line of a bucket is written
+#[derive(Clone)]
+struct Position {
+    file: String,
+    /// The "lines" key of the bucket, for buckets of the configuration file
+    key: Option<String>,
+    /// In the "lines" list, or line of the .bucket file
+    index: usize,
+}
+
+impl Position {
+    fn problem(&self, message: impl Into<String>) -> Problem {
+        let problem = Problem::new(&self.file, message);
+        let problem = match &self.key {
+            Some(key) => problem.at_key(key),
+            None => problem,
+        };
+        problem.at_index(self.index)
+    }
+}
+
+/// A bucket as read from the vault, with the position of each of its lines
+struct CheckedBucket {
+    name: String,
+    /// Where the bucket is defined, to report duplicate names
+    location: String,
+    currency: Option<CurrencyIdent>,
+    lines: Vec<(Position, Line)>,
+}
+
+/// Every problem of the buckets, whether they are defined in the configuration file or in .bucket files
+pub fn check_buckets<V: Vault>(
+    vault: &V,
+    period_config: Option<&PeriodConfigurationVaultValue>,
+    exchange_rates: Option<&ExchangeRates>,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut buckets = read_configuration_buckets(vault, &mut problems);
+    buckets.extend(read_bucket_files(vault, &mut problems));
+
+    for (index, bucket) in buckets.iter().enumerate() {
+        if let Some(other_bucket) = buckets[..index].iter().find(|other_bucket| other_bucket.name == bucket.name) {
+            problems.push(Problem::new(
+                &bucket.location,
+                format!("Bucket \"{}\" is also defined in {}", bucket.name, other_bucket.location),
+            ));
+        }
+        problems.extend(check_bucket(bucket, period_config, exchange_rates));
+    }
+    problems
+}
+
+fn read_configuration_buckets<V: Vault>(vault: &V, problems: &mut Vec<Problem>) -> Vec<CheckedBucket> {
+    // An unreadable configuration file is already reported
+    let Ok(Some(raw_buckets)) = vault.read_optional_vault_values::<Vec<Value>>(BucketsVaultValue::KEY.into()) else {
+        return vec![];
+    };
+
+    let mut buckets = vec![];
+    for (bucket_index, raw_bucket) in raw_buckets.into_iter().enumerate() {
+        let bucket_key = format!("buckets[{}]", bucket_index);
+        let bucket_json: BucketJson = match serde_json::from_value(raw_bucket) {
+            Ok(bucket_json) => bucket_json,
+            Err(error) => {
+                problems.push(Problem::new(CONFIGURATION_FILE, error.to_string()).at_key(&bucket_key));
+                continue;
+            }
+        };
+
+        let lines_key = format!("{}.lines", bucket_key);
+        let mut lines = vec![];
+        for (index, raw_line) in bucket_json.lines.iter().enumerate() {
+            let position = Position { file: CONFIGURATION_FILE.to_string(), key: Some(lines_key.clone()), index };
+            match parse_line(raw_line) {
+                Ok(Some(line)) => lines.push((position, line)),
+                Ok(None) => {}
+                Err(error) => problems.push(position.problem(error.to_string())),
+            }
+        }
+        buckets.push(CheckedBucket {
+            name: bucket_json.name,
+            location: format!("{}: {}", CONFIGURATION_FILE, bucket_key),
+            currency: Some(bucket_json.currency),
+            lines,
+        });
+    }
+    buckets
+}
+
+fn read_bucket_files<V: Vault>(vault: &V, problems: &mut Vec<Problem>) -> Vec<CheckedBucket> {
+    let directory = vault.path().join(BUCKET_DIR);
+    if !directory.is_dir() {
+        return vec![];
+    }
+    let mut paths = match read_dir(&directory) {
+        Ok(dir_reader) => dir_reader
+            .filter_map(|maybe_dir_entry| maybe_dir_entry.ok().map(|dir_entry| dir_entry.path()))
+            .collect::<Vec<PathBuf>>(),
+        Err(why) => {
+            problems.push(Problem::new(BUCKET_DIR, format!("Could not read the Buckets directory: {}", why)));
+            return vec![];
+        }
+    };
+    paths.retain(|path| path.is_file() && path.extension().is_some_and(|extension| extension == BUCKET_FILE_EXTENSION));
+    paths.sort();
+
+    let mut buckets = vec![];
+    for path in paths {
+        let file = vault_file_name(vault, &path);
+        let (Some(name), Ok(content)) = (path.file_stem().and_then(|stem| stem.to_str()), read_to_string(&path)) else {
+            problems.push(Problem::new(&file, "Could not read the file".to_string()));
+            continue;
+        };
+
+        let mut currency = None;
+        let mut lines = vec![];
+        for (index, raw_line) in content.lines().enumerate() {
+            let position = Position { file: file.clone(), key: None, index: index + 1 };
+            match parse_file_line(raw_line) {
+                Ok(Some(FileLine::Currency(_))) if currency.is_some() => {
+                    problems.push(position.problem("The currency is declared more than once".to_string()))
+                }
+                Ok(Some(FileLine::Currency(code))) => currency = Some(code),
+                Ok(Some(FileLine::Line(line))) => lines.push((position, line)),
+                Ok(None) => {}
+                Err(error) => problems.push(position.problem(error.to_string())),
+            }
+        }
+        if currency.is_none() {
+            problems.push(Problem::new(&file, "The bucket does not declare its currency (add a \"CURRENCY <code>\" line)".to_string()));
+        }
+        buckets.push(CheckedBucket { name: name.to_string(), location: file, currency, lines });
+    }
+    buckets
+}
+
+fn check_bucket(
+    bucket: &CheckedBucket,
+    period_config: Option<&PeriodConfigurationVaultValue>,
+    exchange_rates: Option<&ExchangeRates>,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut in_order = true;
+
+    let mut previous_date = None;
+    for (position, Line((date, action))) in bucket.lines.iter() {
+        if previous_date.is_some_and(|previous_date| previous_date > date) {
+            in_order = false;
+            problems.push(position.problem(format!("The line is dated {}, before the line above it", date)));
+        }
+        previous_date = Some(date);
+
+        if let Some(period_config) = period_config {
+            let target_date = match action {
+                Action::SetTarget { target_date, .. } => Some(target_date),
+                _ => None,
+            };
+            for checked_date in [Some(date), target_date].into_iter().flatten() {
+                if let Err(error) = period_config.period_for_date(checked_date) {
+                    problems.push(position.problem(format!("{}: {}", checked_date, error)));
+                }
+            }
+        }
+
+        if let Some(ex) = exchange_rates {
+            if let Err(error) = ex.new_amount_from_raw_amount_at(action_amount(action), date) {
+                problems.push(position.problem(error));
+            }
+        }
+    }
+
+    let (Some(currency), Some(ex)) = (&bucket.currency, exchange_rates) else {
+        return problems;
+    };
+    if let Err(error) = ex.zero(currency) {
+        problems.push(Problem::new(&bucket.location, error));
+        return problems;
+    }
+
+    // Cancellations are only checked once every line could be read, the first one that takes back too much is reported.
+    // Unlike the screens, which wait for the date of a line to come, lines dated after today are checked too.
+    if in_order && problems.is_empty() {
+        for end in 1..=bucket.lines.len() {
+            let prefix = Bucket {
+                name: bucket.name.clone(),
+                currency: currency.clone(),
+                lines: bucket.lines[..end].iter().map(|(_, line)| line.clone()).collect(),
+            };
+            let cancellations = BucketChronoStackWalker::new(&prefix, ex).and_then(|walker| walker.check_cancellations(ChronoStackSlice::All));
+            if let Err(error) = cancellations {
+                problems.push(bucket.lines[end - 1].0.problem(error));
+                break;
+            }
+        }
+    }
+    problems
+}
+
+fn action_amount(action: &Action) -> &RawAmount {
+    match action {
+        Action::Deposit(amount)
+        | Action::DepositCancellation(amount)
+        | Action::Withdrawal(amount)
+        | Action::WithdrawalCancellation(amount)
+        | Action::SetTarget { amount, .. } => amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultImpl;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::fs::{create_dir, write};
+
+    #[test]
+    fn every_problem_is_reported() {
+        let (directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "buckets": [
+                {"name": "Car", "currency": "JPY", "lines": [
+                    "2025/08/01 TARG ¥1000 2024/12/31",
+                    "2025/08/02 DEPO £10",
+                    "2025/08/03 DPEO ¥10"
+                ]},
+                {"name": "Holidays", "currency": "JPY"},
+                {"name": "Bike", "currency": "EUR", "lines": ["2025/08/01 DEPO €10", "2025/08/02 DEPO- €20"]}
+            ]
+        }));
+        create_dir(directory.path().join(BUCKET_DIR)).unwrap();
+        write(
+            directory.path().join(BUCKET_DIR).join("Car.bucket"),
+            "CURRENCY JPY\n2025/08/05 DEPO ¥300\n2025/08/01 WITH ¥100\nCURRENCY EUR\n",
+        ).unwrap();
+        write(directory.path().join(BUCKET_DIR).join("Garden.bucket"), "2025/08/05 DEPO ¥300\n").unwrap();
+
+        let period_config: PeriodConfigurationVaultValue = serde_json::from_value(json!({
+            "type": "fixed_length", "start_date": "2025-01-01", "period_in_days": 14
+        })).unwrap();
+        let problems: Vec<String> = check_buckets(&vault, Some(&period_config), Some(&ExchangeRates::for_tests()))
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect();
+        assert_eq!(problems, vec![
+            "config.json: buckets[0].lines[2]: column 12: expected a tag (TARG, DEPO, DEPO-, WITH or WITH-), found \"DPEO\"",
+            "config.json: buckets[1]: missing field `lines`",
+            "buckets/Car.bucket: line 4: The currency is declared more than once",
+            "buckets/Garden.bucket: The bucket does not declare its currency (add a \"CURRENCY <code>\" line)",
+            "config.json: buckets[0].lines[0]: 2024-12-31: Date is before PeriodsConfiguration's start",
+            "config.json: buckets[0].lines[1]: Could not find currency with sign: £",
+            "config.json: buckets[2].lines[1]: attempt to withdraw more money than the Bucket contains",
+            "buckets/Car.bucket: Bucket \"Car\" is also defined in config.json: buckets[0]",
+            "buckets/Car.bucket: line 3: The line is dated 2025-08-01, before the line above it",
+        ]);
+    }
+}
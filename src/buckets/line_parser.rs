@@ -0,0 +1,319 @@
+use super::{Action, Line};
+use crate::amounts::{CurrencyIdent, RawAmount};
+use chrono::NaiveDate;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_till1, take_while_m_n};
+use nom::character::complete::{alphanumeric1, char, digit1, one_of, space0, space1};
+use nom::combinator::{cut, eof, map, map_res, opt, recognize, rest, value};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
+use nom::IResult;
+use rust_decimal::Decimal;
+use std::fmt::{Display, Formatter};
+
+/*
+Grammar of a bucket line:
+
+    line     := space* (date space+ action space*)? comment? end
+    file     := (line | space* "CURRENCY" space+ code space* comment? end)*
+    action   := "TARG" space+ amount space+ date
+              | ("DEPO" | "DEPO-" | "WITH" | "WITH-") space+ amount
+    date     := YYYY/MM/DD
+    amount   := sign "-"? digits ("," 3 digits)* ("." digits)?
+    comment  := (";" | "#") anything
+
+`space` is a space or a tab. A line made only of spaces and comments holds no action.
+The CURRENCY directive is only found in .bucket files, where it declares the currency of the bucket.
+*/
+
+type ParseResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct LineParseError {
+    /// Starts at 1, counted in characters
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl Display for LineParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "column {}: expected {}, found {}", self.column, self.expected, self.found)
+    }
+}
+
+/// Parse a line of a bucket. Returns None when the line is blank or only holds a comment.
+pub fn parse_line(input: &str) -> Result<Option<Line>, LineParseError> {
+    run(alt((blank_line, map(line_with_action, Some))), input)
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum FileLine {
+    Currency(CurrencyIdent),
+    Line(Line),
+}
+
+/// Parse a line of a .bucket file. Returns None when the line is blank or only holds a comment.
+pub fn parse_file_line(input: &str) -> Result<Option<FileLine>, LineParseError> {
+    run(
+        alt((
+            blank_line,
+            map(currency_directive, |code| Some(FileLine::Currency(code))),
+            map(line_with_action, |line| Some(FileLine::Line(line))),
+        )),
+        input,
+    )
+}
+
+/// Parse an amount written the way lines write it, eg. "¥1,200.50"
+pub fn parse_amount(input: &str) -> Result<RawAmount, LineParseError> {
+    run(terminated(delimited(space0, amount, space0), context("the end of the amount", eof)), input)
+}
+
+fn run<'a, O>(mut parser: impl FnMut(&'a str) -> ParseResult<'a, O>, input: &'a str) -> Result<O, LineParseError> {
+    match parser(input) {
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => Err(to_line_parse_error(input, error)),
+        Err(nom::Err::Incomplete(_)) => Err(LineParseError {
+            column: input.chars().count() + 1,
+            expected: "more input".to_string(),
+            found: "the end of the line".to_string(),
+        }),
+    }
+}
+
+fn blank_line<O>(input: &str) -> ParseResult<'_, Option<O>> {
+    map(tuple((space0, opt(comment), eof)), |_| None)(input)
+}
+
+fn line_end(input: &str) -> ParseResult<'_, ()> {
+    value((), tuple((space0, opt(comment), context("the end of the line", eof))))(input)
+}
+
+fn line_with_action(input: &str) -> ParseResult<'_, Line> {
+    map(
+        delimited(space0, separated_pair(date, separator, action), line_end),
+        |(date, action)| Line((date, action)),
+    )(input)
+}
+
+fn currency_directive(input: &str) -> ParseResult<'_, CurrencyIdent> {
+    // Once the directive is recognised, errors are reported against it rather than against a dated line
+    delimited(
+        pair(space0, tag("CURRENCY")),
+        cut(preceded(separator, context("a currency code", map(alphanumeric1, |code: &str| code.to_string())))),
+        cut(line_end),
+    )(input)
+}
+
+fn to_line_parse_error(input: &str, error: VerboseError<&str>) -> LineParseError {
+    let remaining = error.errors.first().map(|(remaining, _)| *remaining).unwrap_or(input);
+    let expected = error
+        .errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(context.to_string()),
+            _ => None,
+        })
+        .unwrap_or("a valid line".to_string());
+    let found = remaining
+        .split_whitespace()
+        .next()
+        .map(|token| format!("\"{}\"", token))
+        .unwrap_or("the end of the line".to_string());
+
+    LineParseError {
+        column: input[..input.len() - remaining.len()].chars().count() + 1,
+        expected,
+        found,
+    }
+}
+
+fn date(input: &str) -> ParseResult<'_, NaiveDate> {
+    context(
+        "a date (YYYY/MM/DD)",
+        map_res(
+            recognize(tuple((digit1, char('/'), digit1, char('/'), digit1))),
+            |raw_date| NaiveDate::parse_from_str(raw_date, "%Y/%m/%d"),
+        ),
+    )(input)
+}
+
+fn sign(input: &str) -> ParseResult<'_, String> {
+    context(
+        "a currency sign",
+        map(
+            take_till1(|c: char| c.is_ascii_digit() || c == '-' || c.is_whitespace()),
+            |sign: &str| sign.to_string(),
+        ),
+    )(input)
+}
+
+fn figure(input: &str) -> ParseResult<'_, Decimal> {
+    context(
+        "a figure",
+        map_res(
+            recognize(tuple((
+                opt(char('-')),
+                digit1,
+                many0(preceded(char(','), take_while_m_n(3, 3, |c: char| c.is_ascii_digit()))),
+                opt(preceded(char('.'), digit1)),
+            ))),
+            |raw_figure: &str| Decimal::from_str_exact(&raw_figure.replace(',', "")),
+        ),
+    )(input)
+}
+
+fn amount(input: &str) -> ParseResult<'_, RawAmount> {
+    context(
+        "an amount",
+        map(pair(sign, figure), |(sign, figure)| RawAmount { sign, figure }),
+    )(input)
+}
+
+#[derive(Clone, Copy)]
+enum Tag {
+    Target,
+    Deposit,
+    DepositCancellation,
+    Withdrawal,
+    WithdrawalCancellation,
+}
+
+fn line_tag(input: &str) -> ParseResult<'_, Tag> {
+    context(
+        "a tag (TARG, DEPO, DEPO-, WITH or WITH-)",
+        map_res(take_till1(|c: char| c.is_whitespace()), |word| match word {
+            "TARG" => Ok(Tag::Target),
+            "DEPO" => Ok(Tag::Deposit),
+            "DEPO-" => Ok(Tag::DepositCancellation),
+            "WITH" => Ok(Tag::Withdrawal),
+            "WITH-" => Ok(Tag::WithdrawalCancellation),
+            _ => Err(()),
+        }),
+    )(input)
+}
+
+fn separator(input: &str) -> ParseResult<'_, &str> {
+    context("a space", space1)(input)
+}
+
+fn action(input: &str) -> ParseResult<'_, Action> {
+    let (input, line_tag) = line_tag(input)?;
+    let (input, _) = separator(input)?;
+    match line_tag {
+        Tag::Target => map(
+            separated_pair(amount, separator, date),
+            |(amount, target_date)| Action::SetTarget { amount, target_date },
+        )(input),
+        Tag::Deposit => map(amount, Action::Deposit)(input),
+        Tag::DepositCancellation => map(amount, Action::DepositCancellation)(input),
+        Tag::Withdrawal => map(amount, Action::Withdrawal)(input),
+        Tag::WithdrawalCancellation => map(amount, Action::WithdrawalCancellation)(input),
+    }
+}
+
+fn comment(input: &str) -> ParseResult<'_, &str> {
+    preceded(one_of(";#"), rest)(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    fn yen(figure: Decimal) -> RawAmount {
+        RawAmount { sign: "¥".to_string(), figure }
+    }
+
+    fn error(column: usize, expected: &str, found: &str) -> Result<Option<Line>, LineParseError> {
+        Err(LineParseError {
+            column,
+            expected: expected.to_string(),
+            found: found.to_string(),
+        })
+    }
+
+    #[test]
+    fn every_tag() {
+        assert_eq!(
+            parse_line("2023/07/01 TARG ¥200 2023/08/31"),
+            Ok(Some(Line((mkdate(7, 1), Action::SetTarget { amount: yen(dec!(200)), target_date: mkdate(8, 31) }))))
+        );
+        assert_eq!(parse_line("2023/07/01 DEPO ¥200"), Ok(Some(Line((mkdate(7, 1), Action::Deposit(yen(dec!(200))))))));
+        assert_eq!(parse_line("2023/07/01 DEPO- ¥200"), Ok(Some(Line((mkdate(7, 1), Action::DepositCancellation(yen(dec!(200))))))));
+        assert_eq!(parse_line("2023/07/01 WITH ¥200"), Ok(Some(Line((mkdate(7, 1), Action::Withdrawal(yen(dec!(200))))))));
+        assert_eq!(parse_line("2023/07/01 WITH- ¥200"), Ok(Some(Line((mkdate(7, 1), Action::WithdrawalCancellation(yen(dec!(200))))))));
+    }
+
+    #[test]
+    fn whitespace_comments_and_separators() {
+        assert_eq!(
+            parse_line("  2023/07/01\t DEPO   ¥1,200,000.50  ; bonus"),
+            Ok(Some(Line((mkdate(7, 1), Action::Deposit(yen(dec!(1200000.50)))))))
+        );
+        assert_eq!(
+            parse_line("2023/07/01 WITH CHF-12.5 # refund"),
+            Ok(Some(Line((mkdate(7, 1), Action::Withdrawal(RawAmount { sign: "CHF".to_string(), figure: dec!(-12.5) })))))
+        );
+    }
+
+    #[test]
+    fn blank_lines_and_comments() {
+        assert_eq!(parse_line(""), Ok(None));
+        assert_eq!(parse_line(" \t "), Ok(None));
+        assert_eq!(parse_line("; Holidays in Japan"), Ok(None));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_line("2023/13/01 DEPO ¥200"), error(1, "a date (YYYY/MM/DD)", "\"2023/13/01\""));
+        assert_eq!(parse_line("2023/07/01 DEPOT ¥200"), error(12, "a tag (TARG, DEPO, DEPO-, WITH or WITH-)", "\"DEPOT\""));
+        assert_eq!(parse_line("2023/07/01 PUT ¥200"), error(12, "a tag (TARG, DEPO, DEPO-, WITH or WITH-)", "\"PUT\""));
+        assert_eq!(parse_line("2023/07/01 DEPO 200"), error(17, "a currency sign", "\"200\""));
+        assert_eq!(parse_line("2023/07/01 DEPO ¥"), error(18, "a figure", "the end of the line"));
+        assert_eq!(parse_line("2023/07/01 TARG ¥200"), error(21, "a space", "the end of the line"));
+        assert_eq!(parse_line("2023/07/01 DEPO ¥200 ¥300"), error(22, "the end of the line", "\"¥300\""));
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse_amount("¥1,200.50"), Ok(yen(dec!(1200.50))));
+        assert_eq!(parse_amount("1200"), Err(LineParseError {
+            column: 1,
+            expected: "a currency sign".to_string(),
+            found: "\"1200\"".to_string(),
+        }));
+        assert_eq!(parse_amount("¥12 ¥3"), Err(LineParseError {
+            column: 5,
+            expected: "the end of the amount".to_string(),
+            found: "\"¥3\"".to_string(),
+        }));
+    }
+
+    #[test]
+    fn file_lines() {
+        assert_eq!(parse_file_line(" CURRENCY JPY ; yen"), Ok(Some(FileLine::Currency("JPY".to_string()))));
+        assert_eq!(
+            parse_file_line("2023/07/01 DEPO ¥200"),
+            Ok(Some(FileLine::Line(Line((mkdate(7, 1), Action::Deposit(yen(dec!(200))))))))
+        );
+        assert_eq!(parse_file_line("# Holidays"), Ok(None));
+        assert_eq!(parse_file_line("CURRENCY"), Err(LineParseError {
+            column: 9,
+            expected: "a space".to_string(),
+            found: "the end of the line".to_string(),
+        }));
+        assert_eq!(parse_file_line("CURRENCY JPY EUR"), Err(LineParseError {
+            column: 14,
+            expected: "the end of the line".to_string(),
+            found: "\"EUR\"".to_string(),
+        }));
+    }
+}
@@ -0,0 +1,248 @@
+use super::line_parser::{parse_amount, parse_file_line, parse_line, FileLine};
+use super::{Action, Bucket, BucketsVaultValue, Line, BUCKET_DIR, BUCKET_FILE_EXTENSION};
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::RawAmount;
+use crate::error::ChoError;
+use crate::period::PeriodConfigurationVaultValue;
+use crate::vault::{write_atomically, Vault, VaultReadable};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+/// A line to add to a bucket
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NewLine {
+    Deposit,
+    DepositCancellation,
+    Withdrawal,
+    WithdrawalCancellation,
+    SetTarget { target_date: NaiveDate },
+}
+
+/// Where the lines of a bucket are written
+enum BucketSource {
+    File(PathBuf),
+    /// Index of the bucket in the "buckets" key of the configuration file
+    Configuration(usize),
+}
+
+/// Add a line to a bucket, once the bucket with the line is known to be valid. Lines are kept in chronological
+/// order, the new line goes after the ones of the same day. Amounts written without a sign are in the bucket's currency.
+/// Returns the line as it was written.
+pub fn record_line<V: Vault>(
+    vault: &V,
+    bucket_name: &str,
+    new_line: NewLine,
+    raw_amount: &str,
+    date: &NaiveDate,
+    today: &NaiveDate,
+    ex: &ExchangeRates,
+) -> Result<String, ChoError> {
+    let period_config = PeriodConfigurationVaultValue::from_vault(vault)?;
+    let bucket = BucketsVaultValue::from_vault(vault)?
+        .into_iter()
+        .find(|bucket| bucket.name == bucket_name)
+        .ok_or(format!("No bucket is named \"{}\"", bucket_name))?;
+
+    let amount = line_amount(raw_amount, &bucket, ex)?;
+    let action = match new_line {
+        NewLine::Deposit => Action::Deposit(amount),
+        NewLine::DepositCancellation => Action::DepositCancellation(amount),
+        NewLine::Withdrawal => Action::Withdrawal(amount),
+        NewLine::WithdrawalCancellation => Action::WithdrawalCancellation(amount),
+        NewLine::SetTarget { target_date } => Action::SetTarget { amount, target_date },
+    };
+    let line = Line((*date, action)).to_string();
+
+    match find_bucket_source(vault, bucket_name)? {
+        BucketSource::File(path) => {
+            let content = read_to_string(&path).map_err(|source| ChoError::File { path: path.clone(), source })?;
+            let raw_lines: Vec<&str> = content.lines().collect();
+            let position = insert_position(&raw_lines, date, |raw_line| match parse_file_line(raw_line) {
+                Ok(Some(FileLine::Line(Line((line_date, _))))) => Some(line_date),
+                _ => None,
+            });
+            let mut raw_lines: Vec<String> = raw_lines.into_iter().map(String::from).collect();
+            raw_lines.insert(position, line.clone());
+            let content = raw_lines.join("\n") + "\n";
+
+            check(&Bucket::from_bucket_file(bucket_name, &content)?, date, &period_config, today, ex)?;
+            write_atomically(&path, &content)?;
+        }
+        BucketSource::Configuration(index) => {
+            let mut buckets: Vec<Value> = vault.read_vault_values(BucketsVaultValue::KEY.into())?;
+            let lines = buckets[index]
+                .get_mut("lines")
+                .and_then(Value::as_array_mut)
+                .ok_or(format!("Bucket \"{}\" has no list of lines", bucket_name))?;
+            let position = insert_position(lines, date, |raw_line| match raw_line.as_str().map(parse_line) {
+                Some(Ok(Some(Line((line_date, _))))) => Some(line_date),
+                _ => None,
+            });
+            lines.insert(position, Value::String(line.clone()));
+
+            let edited_bucket: Bucket = serde_json::from_value(buckets[index].clone())?;
+            check(&edited_bucket, date, &period_config, today, ex)?;
+            vault.write_vault_values(BucketsVaultValue::KEY.into(), &buckets)?;
+        }
+    }
+
+    Ok(line)
+}
+
+/// The amount as it is written in the line
+fn line_amount(raw_amount: &str, bucket: &Bucket, ex: &ExchangeRates) -> Result<RawAmount, ChoError> {
+    let amount = match Decimal::from_str_exact(&raw_amount.trim().replace(',', "")) {
+        Ok(figure) => RawAmount { sign: ex.zero(&bucket.currency)?.to_raw_amount().sign, figure },
+        Err(_) => parse_amount(raw_amount).map_err(|error| format!("Invalid amount \"{}\", {}", raw_amount, error))?,
+    };
+    if amount.figure <= Decimal::ZERO {
+        return Err(format!("Invalid amount \"{}\", it should be more than zero", raw_amount).into());
+    }
+    Ok(amount)
+}
+
+fn find_bucket_source<V: Vault>(vault: &V, bucket_name: &str) -> Result<BucketSource, ChoError> {
+    let path = vault
+        .path()
+        .join(BUCKET_DIR)
+        .join(bucket_name)
+        .with_extension(BUCKET_FILE_EXTENSION);
+    if path.is_file() {
+        return Ok(BucketSource::File(path));
+    }
+
+    let buckets: Vec<Value> = vault.read_optional_vault_values(BucketsVaultValue::KEY.into())?.unwrap_or_default();
+    buckets
+        .iter()
+        .position(|bucket| bucket.get("name").and_then(Value::as_str) == Some(bucket_name))
+        .map(BucketSource::Configuration)
+        .ok_or(format!("No bucket is named \"{}\"", bucket_name).into())
+}
+
+/// After the last line dated on or before the date. Before the first dated line when there is none, so that
+/// the lines before it (eg. the currency, comments) stay at the top.
+fn insert_position<T>(raw_lines: &[T], date: &NaiveDate, line_date: impl Fn(&T) -> Option<NaiveDate>) -> usize {
+    let dated_lines: Vec<(usize, NaiveDate)> = raw_lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, raw_line)| line_date(raw_line).map(|line_date| (index, line_date)))
+        .collect();
+    match dated_lines.iter().rev().find(|(_, line_date)| line_date <= date) {
+        Some((index, _)) => index + 1,
+        None => dated_lines.first().map(|(index, _)| *index).unwrap_or(raw_lines.len()),
+    }
+}
+
+/// The bucket must still be valid with the new line, as the remaining screen will read it on the line's date
+fn check(
+    bucket: &Bucket,
+    date: &NaiveDate,
+    period_config: &PeriodConfigurationVaultValue,
+    today: &NaiveDate,
+    ex: &ExchangeRates,
+) -> Result<(), ChoError> {
+    bucket.for_period(period_config, today.max(date), ex)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultImpl;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::fs::{create_dir, write};
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    fn vault() -> (tempfile::TempDir, VaultImpl) {
+        let (directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "periods_configuration": {"type": "monthly"},
+            "buckets": [{"name": "Car", "currency": "JPY", "lines": ["2025/08/01 TARG ¥1000 2025/12/31"]}]
+        }));
+        create_dir(directory.path().join(BUCKET_DIR)).unwrap();
+        write(
+            directory.path().join(BUCKET_DIR).join("Holidays.bucket"),
+            "; Summer holidays\nCURRENCY JPY\n2025/08/01 DEPO ¥300\n2025/09/10 WITH ¥100 ; train\n",
+        ).unwrap();
+        (directory, vault)
+    }
+
+    fn record(vault: &VaultImpl, bucket_name: &str, new_line: NewLine, raw_amount: &str, date: NaiveDate) -> Result<String, ChoError> {
+        record_line(
+            vault,
+            bucket_name,
+            new_line,
+            raw_amount,
+            &date,
+            &mkdate(9, 15),
+            &ExchangeRates::for_tests(),
+        )
+    }
+
+    #[test]
+    fn bucket_file() {
+        let (directory, vault) = vault();
+        assert_eq!(record(&vault, "Holidays", NewLine::Deposit, "150", mkdate(9, 1)), Ok("2025/09/01 DEPO ¥150".to_string()));
+        assert_eq!(
+            record(&vault, "Holidays", NewLine::WithdrawalCancellation, "¥20.5", mkdate(9, 12)),
+            Ok("2025/09/12 WITH- ¥20.5".to_string())
+        );
+        assert_eq!(
+            read_to_string(directory.path().join(BUCKET_DIR).join("Holidays.bucket")).unwrap(),
+            "; Summer holidays\nCURRENCY JPY\n2025/08/01 DEPO ¥300\n2025/09/01 DEPO ¥150\n2025/09/10 WITH ¥100 ; train\n2025/09/12 WITH- ¥20.5\n"
+        );
+    }
+
+    #[test]
+    fn withdraw_more_than_the_bucket_contains() {
+        // Like the remaining screen, only cancellations are checked against what the bucket contains
+        let (_directory, vault) = vault();
+        assert_eq!(
+            record(&vault, "Holidays", NewLine::Withdrawal, "¥250", mkdate(9, 1)),
+            Ok("2025/09/01 WITH ¥250".to_string())
+        );
+    }
+
+    #[test]
+    fn configuration() {
+        let (_directory, vault) = vault();
+        assert_eq!(
+            record(&vault, "Car", NewLine::SetTarget { target_date: mkdate(12, 31) }, "¥2,000", mkdate(7, 1)),
+            Ok("2025/07/01 TARG ¥2000 2025/12/31".to_string())
+        );
+        let buckets: Vec<Value> = vault.read_vault_values("buckets".into()).unwrap();
+        assert_eq!(buckets[0]["lines"], json!(["2025/07/01 TARG ¥2000 2025/12/31", "2025/08/01 TARG ¥1000 2025/12/31"]));
+    }
+
+    #[test]
+    fn invalid_lines_are_not_written() {
+        let (directory, vault) = vault();
+        assert_eq!(
+            record(&vault, "Holidays", NewLine::DepositCancellation, "¥350", mkdate(9, 1)),
+            Err("attempt to withdraw more money than the Bucket contains".into())
+        );
+        assert_eq!(
+            record(&vault, "Holidays", NewLine::DepositCancellation, "¥250", mkdate(9, 12)),
+            Err("attempt to withdraw more money than the Bucket contains".into())
+        );
+        assert_eq!(
+            record(&vault, "Car", NewLine::WithdrawalCancellation, "¥10", mkdate(9, 12)),
+            Err("attempt to put back money that was not withdrawn".into())
+        );
+        assert_eq!(
+            record(&vault, "Car", NewLine::Deposit, "-10", mkdate(9, 12)),
+            Err("Invalid amount \"-10\", it should be more than zero".into())
+        );
+        assert_eq!(record(&vault, "Boat", NewLine::Deposit, "10", mkdate(9, 12)), Err("No bucket is named \"Boat\"".into()));
+        assert_eq!(
+            read_to_string(directory.path().join(BUCKET_DIR).join("Holidays.bucket")).unwrap(),
+            "; Summer holidays\nCURRENCY JPY\n2025/08/01 DEPO ¥300\n2025/09/10 WITH ¥100 ; train\n"
+        );
+    }
+}
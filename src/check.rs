@@ -0,0 +1,192 @@
+use crate::accounts::checking::check_accounts;
+use crate::amounts::currency_table::{CurrencyTable, CustomCurrenciesVaultValue};
+use crate::amounts::exchange_rates::{ExchangeRates, ExchangeRatesVaultValue};
+use crate::buckets::checking::check_buckets;
+use crate::error::ChoError;
+use crate::ignored_transaction::{IgnoredTransaction, IgnoredTransactionsVaultValues};
+use crate::import::csv::CsvProfilesVaultValue;
+use crate::period::PeriodConfigurationVaultValue;
+use crate::predicted_income::PredictedIncome;
+use crate::recurring_expense::{RecurringExpense, RecurringExpensesVaultValue};
+use crate::vault::{Vault, VaultReadable};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub const CONFIGURATION_FILE: &str = "config.json";
+
+/// Something wrong in the vault, and where it is
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Problem {
+    /// Relative to the vault, eg. "accounts/current.json"
+    pub file: String,
+    /// Path of the value in a JSON file, eg. "buckets[2].lines"
+    pub key: Option<String>,
+    /// Position in the list under the key, from 0. When there is no key, line of the file, from 1.
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(file: &str, message: impl Into<String>) -> Problem {
+        Problem { file: file.to_string(), key: None, index: None, message: message.into() }
+    }
+
+    pub fn at_key(mut self, key: &str) -> Problem {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn at_index(mut self, index: usize) -> Problem {
+        self.index = Some(index);
+        self
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+        match (&self.key, self.index) {
+            (Some(key), Some(index)) => write!(f, ": {}[{}]", key, index)?,
+            (Some(key), None) => write!(f, ": {}", key)?,
+            (None, Some(line)) => write!(f, ": line {}", line)?,
+            (None, None) => {}
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The path of a file of the vault, relative to the vault
+pub fn vault_file_name<V: Vault>(vault: &V, path: &Path) -> String {
+    path.strip_prefix(vault.path()).unwrap_or(path).display().to_string()
+}
+
+/// Load every component of the vault and report all the problems found, instead of stopping at the first one.
+/// `exchange_rates` are the rates the other commands would use, the amounts of the vault are checked with them.
+pub fn check_vault<V: Vault>(vault: &V, exchange_rates: Result<ExchangeRates, ChoError>) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    let configuration_path = vault.path().join(CONFIGURATION_FILE);
+    let configuration = read_to_string(&configuration_path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| serde_json::from_str::<Map<String, Value>>(&content).map_err(|error| error.to_string()));
+    let period_config = match configuration {
+        Ok(configuration) => {
+            check_list::<IgnoredTransaction>(&configuration, IgnoredTransactionsVaultValues::KEY, true, &mut problems);
+            check_list::<RecurringExpense>(&configuration, RecurringExpensesVaultValue::KEY, false, &mut problems);
+            check_value::<PredictedIncome>(&configuration, PredictedIncome::KEY, false, &mut problems);
+            check_value::<CsvProfilesVaultValue>(&configuration, CsvProfilesVaultValue::KEY, false, &mut problems);
+            check_value::<CustomCurrenciesVaultValue>(&configuration, CustomCurrenciesVaultValue::KEY, false, &mut problems);
+            check_value::<ExchangeRatesVaultValue>(&configuration, ExchangeRatesVaultValue::KEY, false, &mut problems);
+            check_value::<PeriodConfigurationVaultValue>(&configuration, PeriodConfigurationVaultValue::KEY, true, &mut problems)
+        }
+        Err(error) => {
+            problems.push(Problem::new(CONFIGURATION_FILE, format!("Could not read the file: {}", error)));
+            None
+        }
+    };
+
+    // Problems of the currencies and rates of the vault are already reported above
+    let exchange_rates = match (CurrencyTable::from_vault(vault), exchange_rates) {
+        (Ok(_), Err(error)) => {
+            problems.push(Problem::new(CONFIGURATION_FILE, error).at_key(ExchangeRatesVaultValue::KEY));
+            None
+        }
+        (_, exchange_rates) => exchange_rates.ok(),
+    };
+
+    problems.extend(check_accounts(vault, period_config.as_ref(), exchange_rates.as_ref()));
+    problems.extend(check_buckets(vault, period_config.as_ref(), exchange_rates.as_ref()));
+    problems
+}
+
+/// Decode the value of a key of the configuration file
+fn check_value<T: DeserializeOwned>(
+    configuration: &Map<String, Value>,
+    key: &str,
+    required: bool,
+    problems: &mut Vec<Problem>,
+) -> Option<T> {
+    match configuration.get(key) {
+        None if required => {
+            problems.push(Problem::new(CONFIGURATION_FILE, "The key is missing".to_string()).at_key(key));
+            None
+        }
+        None => None,
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|error| problems.push(Problem::new(CONFIGURATION_FILE, error.to_string()).at_key(key)))
+            .ok(),
+    }
+}
+
+/// Decode each item of a list of the configuration file
+fn check_list<T: DeserializeOwned>(configuration: &Map<String, Value>, key: &str, required: bool, problems: &mut Vec<Problem>) {
+    let Some(items) = check_value::<Vec<Value>>(configuration, key, required, problems) else {
+        return;
+    };
+    for (index, item) in items.into_iter().enumerate() {
+        if let Err(error) = serde_json::from_value::<T>(item) {
+            problems.push(Problem::new(CONFIGURATION_FILE, error.to_string()).at_key(key).at_index(index));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultImpl;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn problems(configuration: Value) -> Vec<String> {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(configuration);
+        check_vault(&vault, Ok(ExchangeRates::for_tests()))
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn configuration() {
+        assert_eq!(
+            problems(json!({
+                "periods_configuration": {"type": "weekly"},
+                "ignored_transactions": [
+                    {"name": "Gift", "currency": "EUR", "amount": "50", "date": "2025-09-01"},
+                    {"name": "Refund", "currency": "EUR", "amount": "12"}
+                ],
+                "recurring_expenses": [
+                    {"name": "Rent", "currency": "EUR", "amount": "800"},
+                    {"name": "Gym", "currency": "EUR", "amount": "30", "frequency": {"monthly": {"day": 0}}},
+                    {"name": "Tax", "currency": "EUR", "amount": "300", "frequency": {"yearly": {"month": 13, "day": 1}}}
+                ],
+                "predicted_income": {"currency": "EUR", "figure": "2000", "yearly": [{"month": 4, "day": 31, "figure": "100"}]}
+            })),
+            vec![
+                "config.json: ignored_transactions[1]: missing field `date`",
+                "config.json: recurring_expenses[0]: missing field `frequency`",
+                "config.json: recurring_expenses[1]: A monthly expense cannot be paid on day 0, it must be between 1 and 31",
+                "config.json: recurring_expenses[2]: Month 13 and day 1 are not a date",
+                "config.json: predicted_income: Month 4 and day 31 are not a date",
+                "config.json: periods_configuration: unknown variant `weekly`, expected `fixed_length` or `monthly`",
+                "accounts: Could not read the Accounts directory: No such file or directory (os error 2)",
+            ]
+        );
+
+        assert_eq!(problems(json!({"periods_configuration": {"type": "monthly"}})), vec![
+            "config.json: ignored_transactions: The key is missing",
+            "accounts: Could not read the Accounts directory: No such file or directory (os error 2)",
+        ]);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Problem::new("buckets/Car.bucket", "Oops".to_string()).at_index(3).to_string(),
+            "buckets/Car.bucket: line 3: Oops"
+        );
+        assert_eq!(Problem::new("accounts", "Oops".to_string()).to_string(), "accounts: Oops");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::ChoError;
+use crate::period::Period;
+use chrono::NaiveDate;
+
+/// Something that happened on a given date
+pub type CalendarEntry<T> = (NaiveDate, T);
+
+pub trait ChronoStackVisitor<T> {
+    fn visit(&mut self, date: &NaiveDate, entry: &T) -> Result<(), ChoError>;
+}
+
+/// The entries of the stack that are handed to the visitor
+#[derive(Clone, Copy, Debug)]
+pub enum ChronoStackSlice<'a> {
+    /// Entries dated within the period
+    InPeriod(&'a Period),
+    /// Entries dated before the start of the period
+    UntilPeriod(&'a Period),
+    /// Entries dated on or before the date
+    UntilDate(&'a NaiveDate),
+    All,
+}
+
+impl ChronoStackSlice<'_> {
+    fn contains(&self, date: &NaiveDate) -> bool {
+        match self {
+            ChronoStackSlice::InPeriod(period) => period.contains(date),
+            ChronoStackSlice::UntilPeriod(period) => date < &period.start_date,
+            ChronoStackSlice::UntilDate(until) => date <= until,
+            ChronoStackSlice::All => true,
+        }
+    }
+}
+
+/// Hands a slice of a list of calendar entries to visitors, in chronological order.
+/// The order of the entries is checked once, over the whole list, when the walker is built.
+pub struct ChronoStackWalker<'a, T> {
+    entries: Vec<&'a CalendarEntry<T>>,
+}
+
+impl<'a, T> ChronoStackWalker<'a, T> {
+    pub fn new(entries: impl IntoIterator<Item = &'a CalendarEntry<T>>) -> Result<ChronoStackWalker<'a, T>, ChoError> {
+        let entries: Vec<&CalendarEntry<T>> = entries.into_iter().collect();
+
+        if let Some(unordered) = entries.windows(2).find(|pair| pair[1].0 < pair[0].0) {
+            return Err(format!(
+                "entries are not in chronological order: {} comes after {}",
+                unordered[1].0, unordered[0].0
+            ).into());
+        }
+
+        Ok(ChronoStackWalker { entries })
+    }
+
+    pub fn walk(&self, slice: ChronoStackSlice, visitor: &mut impl ChronoStackVisitor<T>) -> Result<(), ChoError> {
+        self.entries
+            .iter()
+            .filter(|(date, _)| slice.contains(date))
+            .try_for_each(|(date, entry)| visitor.visit(date, entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    #[derive(Default)]
+    struct Collector {
+        visited: Vec<&'static str>,
+    }
+
+    impl ChronoStackVisitor<&'static str> for Collector {
+        fn visit(&mut self, _date: &NaiveDate, entry: &&'static str) -> Result<(), ChoError> {
+            self.visited.push(entry);
+            Ok(())
+        }
+    }
+
+    fn walk(entries: &[CalendarEntry<&'static str>], slice: ChronoStackSlice) -> Vec<&'static str> {
+        let mut collector = Collector::default();
+        ChronoStackWalker::new(entries)
+            .and_then(|walker| walker.walk(slice, &mut collector))
+            .expect("Can walk the entries");
+        collector.visited
+    }
+
+    #[test]
+    fn slices() {
+        let entries = [
+            (mkdate(8, 31), "august"),
+            (mkdate(9, 1), "first of september"),
+            (mkdate(9, 15), "today"),
+            (mkdate(9, 15), "also today"),
+            (mkdate(10, 1), "october"),
+        ];
+        let september = Period { start_date: mkdate(9, 1), end_date: mkdate(9, 30) };
+
+        assert_eq!(walk(&entries, ChronoStackSlice::InPeriod(&september)), vec!["first of september", "today", "also today"]);
+        assert_eq!(walk(&entries, ChronoStackSlice::UntilPeriod(&september)), vec!["august"]);
+        assert_eq!(walk(&entries, ChronoStackSlice::UntilDate(&mkdate(9, 15))), vec!["august", "first of september", "today", "also today"]);
+        assert_eq!(walk(&entries, ChronoStackSlice::All).len(), 5);
+    }
+
+    #[test]
+    fn entries_out_of_order() {
+        let entries = [(mkdate(9, 1), "first"), (mkdate(10, 1), "second"), (mkdate(9, 15), "third")];
+
+        assert_eq!(
+            ChronoStackWalker::new(&entries).err(),
+            Some("entries are not in chronological order: 2025-09-15 comes after 2025-10-01".into())
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use rust_decimal::Decimal;
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 fn parse_exchange_rate(s: &str) -> Result<(String, Decimal), String> {
@@ -25,16 +26,219 @@ fn parse_exchange_rate(s: &str) -> Result<(String, Decimal), String> {
 
 #[derive(Parser)]
 #[command()]
-pub struct RemainingOptions {
-    #[arg(short = 'r', long = "exchange-rate", value_parser = parse_exchange_rate)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalOptions,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Options shared by every subcommand. They can be passed before or after the subcommand name.
+#[derive(Args)]
+pub struct GlobalOptions {
+    /// Rate of a currency, used instead of the rates stored in the vault
+    #[arg(short = 'r', long = "exchange-rate", value_parser = parse_exchange_rate, global = true)]
     pub exchange_rates: Vec<(String, Decimal)>,
 
-    #[arg(short = 't', long = "target-currency")]
-    pub target_currency: String,
+    #[arg(short = 't', long = "target-currency", global = true)]
+    pub target_currency: Option<String>,
 
+    #[arg(short = 'V', long, global = true)]
+    pub vault: Option<PathBuf>,
+}
+
+impl GlobalOptions {
+    pub fn target_currency(&self) -> Result<&String, String> {
+        self.target_currency
+            .as_ref()
+            .ok_or("This command requires a target currency (--target-currency)".to_string())
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Show how much money remains to be spent this period
+    Remaining(RemainingOptions),
+    /// Show how each account evolved this period
+    Accounts(AccountsOptions),
+    /// Show the state of each bucket this period
+    Buckets(BucketsOptions),
+    /// Show the current period and the ones following it
+    Periods(PeriodsOptions),
+    /// Check whether the goals of the buckets can be reached with the predicted income
+    Feasibility,
+    /// Project the interest the savings accounts will earn
+    Savings(SavingsOptions),
+    /// Project the money held in the accounts and set aside in the buckets, period by period
+    Forecast(ForecastOptions),
+    /// Add the balances of a bank statement to the history of an account
+    Import(ImportOptions),
+    /// Load every value of the vault and report all the problems found
+    #[command(visible_alias = "validate")]
+    Check,
+}
+
+#[derive(Args)]
+pub struct RemainingOptions {
     #[arg(short = 'p', long = "include-predicted")]
     pub include_predicted_income: bool,
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
 
-    #[arg(short = 'V', long)]
-    pub vault: Option<PathBuf>,
-}
\ No newline at end of file
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Tables, to be read
+    Text,
+    /// A JSON document, to be used by other programs. Its schema is described in the README.
+    Json,
+}
+
+#[derive(Args)]
+pub struct PeriodsOptions {
+    /// Number of periods to show after the current one
+    #[arg(short = 'n', long = "next", default_value_t = 3)]
+    pub next: u16,
+}
+
+#[derive(Args)]
+pub struct SavingsOptions {
+    /// Number of months to project the interest over
+    #[arg(short = 'm', long = "months", default_value_t = 12)]
+    pub months: u32,
+}
+
+#[derive(Args)]
+pub struct ForecastOptions {
+    /// Last day of the forecast, eg. 2026-12-31
+    #[arg(short = 'u', long = "until")]
+    pub until: NaiveDate,
+}
+
+#[derive(Args)]
+pub struct ImportOptions {
+    #[command(subcommand)]
+    pub command: ImportCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Import a CSV export, read with one of the profiles of the vault
+    Csv(CsvImportOptions),
+    /// Import an OFX or QFX export
+    Ofx(OfxImportOptions),
+}
+
+#[derive(Args)]
+pub struct CsvImportOptions {
+    /// Name of the account the statement belongs to
+    #[arg(short = 'a', long = "account")]
+    pub account: String,
+
+    /// Name of the profile describing the columns of the file
+    #[arg(short = 'p', long = "profile")]
+    pub profile: String,
+
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct OfxImportOptions {
+    /// Name of the account the statement belongs to
+    #[arg(short = 'a', long = "account")]
+    pub account: String,
+
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct AccountsOptions {
+    #[command(subcommand)]
+    pub command: Option<AccountsCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum AccountsCommand {
+    /// Record the amount an account holds on a date, creating the account if needed
+    Record(AccountRecordOptions),
+}
+
+#[derive(Args)]
+pub struct AccountRecordOptions {
+    /// Name of the account
+    pub name: String,
+
+    /// Amount held on the account, or owed for credit cards and loans
+    #[arg(allow_negative_numbers = true)]
+    pub amount: Decimal,
+
+    /// Date of the amount, today if not given
+    #[arg(short = 'd', long = "date")]
+    pub date: Option<NaiveDate>,
+
+    /// Currency of the account, required to create it
+    #[arg(short = 'c', long = "currency")]
+    pub currency: Option<String>,
+
+    /// Replace the amount already recorded on that date
+    #[arg(long = "overwrite")]
+    pub overwrite: bool,
+}
+
+#[derive(Args)]
+pub struct BucketsOptions {
+    #[command(subcommand)]
+    pub command: Option<BucketsCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum BucketsCommand {
+    /// Show what happened to a bucket in each period since its first line
+    History(BucketHistoryOptions),
+    /// Record money put in a bucket
+    Deposit(BucketLineOptions),
+    /// Record money taken out of a bucket
+    Withdraw(BucketLineOptions),
+    /// Take back part of a deposit recorded by mistake
+    CancelDeposit(BucketLineOptions),
+    /// Put back part of a withdrawal recorded by mistake
+    CancelWithdrawal(BucketLineOptions),
+    /// Set the amount the bucket should contain by a date
+    SetTarget(BucketTargetOptions),
+}
+
+#[derive(Args)]
+pub struct BucketLineOptions {
+    /// Name of the bucket
+    pub name: String,
+
+    /// Amount, eg. ¥1500 or 1500 for the currency of the bucket
+    pub amount: String,
+
+    /// Date of the line, today if not given
+    #[arg(short = 'd', long = "date")]
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Args)]
+pub struct BucketTargetOptions {
+    /// Name of the bucket
+    pub name: String,
+
+    /// Amount, eg. ¥100000 or 100000 for the currency of the bucket
+    pub amount: String,
+
+    /// Date by which the bucket should contain the amount, eg. 2026-12-31
+    pub target_date: NaiveDate,
+
+    /// Date of the line, today if not given
+    #[arg(short = 'd', long = "date")]
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Args)]
+pub struct BucketHistoryOptions {
+    /// Name of the bucket
+    pub name: String,
+}
@@ -1,59 +1,43 @@
-use crate::remaining_operation::core_types::{IllustrationValue, RemainingOperationScreen};
+use crate::amounts::Amount;
+use crate::accounts::savings::ProjectedInterest;
+use crate::buckets::BucketHistoryRow;
+use crate::check::Problem;
+use crate::forecast::{Balance, ProjectedPeriod};
+use crate::goal_feasibility::PeriodCommitment;
+use crate::accounts::recording::RecordedAmount;
+use crate::accounts::transactions::NotableTransaction;
+use crate::accounts::Figure;
+use crate::ignored_transaction::IgnoredTransaction;
+use crate::import::ImportReport;
+use crate::period::Period;
+use crate::remaining_operation::core_types::{IllustrationValue, RemainingOperationScreen, RemainingOperationScreenGroup};
+use chrono::NaiveDate;
 use comfy_table::Table;
-use serde_json::to_string;
+use serde::Serialize;
+use serde_json::{json, to_string, to_string_pretty, Map, Value};
 
-pub fn format_remaining_operation_screen(screen: &RemainingOperationScreen) -> String {
+pub fn format_remaining_operation_screen(screen: &RemainingOperationScreen, largest_transactions: &[NotableTransaction]) -> String {
     let mut components = vec![title(&format!(
         "Current period : {} to {}",
         screen.period.start_date, screen.period.end_date,
     ))];
 
     for group in screen.groups.iter() {
-        let group_title = title(&group.name);
-        let content = if !group.empty() {
-            let mut table = Table::new();
+        components.push(format_group(group));
+    }
 
-            let mut illustration_fields = vec![
-                String::from("Name"),
-                String::from("Amount")
-            ];
-            illustration_fields.extend(group.illustration_fields.clone());
-            table.set_header(illustration_fields);
-
-            for operand in group.operands.iter() {
-                let mut illustration_values = vec![
-                    operand.name.clone(),
-                    operand.amount.to_string()
-                ];
-
-                let raw_illustration_value = operand
-                    .illustration
-                    .clone()
-                    .into_iter()
-                    .map(|(_, value)| value)
-                    .map(|illustration_value| match illustration_value {
-                        IllustrationValue::Amount(amount) => amount.to_string(),
-                        IllustrationValue::NullAmount => "-".to_string(),
-                        IllustrationValue::Bool(bool) => (if bool { "✅" } else { "" }).into(),
-                        IllustrationValue::Date(date) => date.to_string(),
-                    });
-
-                illustration_values.extend(raw_illustration_value);
-                table.add_row(illustration_values);
-            }
-            
-            let mut total_row = vec![
-                "Total".to_string(),
-                group.total.to_string()
-            ];
-            total_row.extend(group.illustration_fields.iter().map(|_| "".to_string()));
-            table.add_row(total_row);
-            
-            table.to_string()
-        } else {
-            "No operands for this period".to_string()
-        };
-        components.push(format!("{}\n{}", group_title, content));
+    if !largest_transactions.is_empty() {
+        let mut table = Table::new();
+        table.set_header(vec!["Account", "Date", "Amount", "Description"]);
+        for transaction in largest_transactions {
+            table.add_row(vec![
+                transaction.account.clone(),
+                transaction.date.to_string(),
+                transaction.amount.to_string(),
+                transaction.description.clone(),
+            ]);
+        }
+        components.push(format!("{}\n{}", title("Largest transactions"), table));
     }
 
     components.push(title(&format!(
@@ -66,6 +50,373 @@ pub fn format_remaining_operation_screen(screen: &RemainingOperationScreen) -> S
     components.join("\n\n")
 }
 
+/// Raised whenever a field of the JSON remaining screen is removed or changes meaning. Adding fields keeps the version.
+const REMAINING_SCREEN_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct RemainingScreenJson<'a> {
+    schema_version: u32,
+    period: &'a Period,
+    groups: Vec<GroupJson<'a>>,
+    largest_transactions: Vec<NotableTransactionJson<'a>>,
+    remaining: &'a Amount,
+}
+
+#[derive(Serialize)]
+struct GroupJson<'a> {
+    name: &'a str,
+    illustration_fields: &'a [String],
+    operands: Vec<OperandJson<'a>>,
+    total: &'a Amount,
+}
+
+#[derive(Serialize)]
+struct OperandJson<'a> {
+    name: &'a str,
+    amount: &'a Amount,
+    /// Keyed by illustration field, in the order of the fields
+    illustration: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct NotableTransactionJson<'a> {
+    account: &'a str,
+    date: NaiveDate,
+    amount: &'a Amount,
+    description: &'a str,
+}
+
+/// The remaining screen as a JSON document, for other programs. The schema is described in the README.
+pub fn format_remaining_operation_json(screen: &RemainingOperationScreen, largest_transactions: &[NotableTransaction]) -> Result<String, String> {
+    let groups = screen
+        .groups
+        .iter()
+        .map(|group| GroupJson {
+            name: &group.name,
+            illustration_fields: &group.illustration_fields,
+            operands: group
+                .operands
+                .iter()
+                .map(|operand| OperandJson {
+                    name: &operand.name,
+                    amount: &operand.amount,
+                    illustration: operand
+                        .illustration
+                        .iter()
+                        .map(|(field, value)| (field.clone(), illustration_json(value)))
+                        .collect(),
+                })
+                .collect(),
+            total: &group.total,
+        })
+        .collect();
+    let largest_transactions = largest_transactions
+        .iter()
+        .map(|transaction| NotableTransactionJson {
+            account: &transaction.account,
+            date: transaction.date,
+            amount: &transaction.amount,
+            description: &transaction.description,
+        })
+        .collect();
+
+    let screen = RemainingScreenJson {
+        schema_version: REMAINING_SCREEN_SCHEMA_VERSION,
+        period: &screen.period,
+        groups,
+        largest_transactions,
+        remaining: &screen.remaining,
+    };
+    to_string_pretty(&screen)
+        .map(|json| json + "\n")
+        .map_err(|error| format!("Could not write the screen as JSON: {}", error))
+}
+
+/// Amounts are objects, missing amounts and dates are null
+fn illustration_json(value: &IllustrationValue) -> Value {
+    match value {
+        IllustrationValue::Amount(amount) => json!(amount),
+        IllustrationValue::NullAmount | IllustrationValue::NullDate => Value::Null,
+        IllustrationValue::Bool(bool) => Value::Bool(*bool),
+        IllustrationValue::Date(date) => Value::String(date.to_string()),
+    }
+}
+
+pub fn format_group_screen(period: &Period, group: &RemainingOperationScreenGroup) -> String {
+    [
+        title(&format!(
+            "Current period : {} to {}",
+            period.start_date, period.end_date,
+        )),
+        format_group(group),
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_periods_screen(periods: &[Period]) -> String {
+    let mut table = Table::new();
+    table.set_header(vec!["", "Start", "End"]);
+
+    for (index, period) in periods.iter().enumerate() {
+        let label = if index == 0 { "Current" } else { "" };
+        table.add_row(vec![
+            label.to_string(),
+            period.start_date.to_string(),
+            period.end_date.to_string(),
+        ]);
+    }
+
+    [
+        title("Periods"),
+        table.to_string(),
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_account_record_screen(name: &str, figure: Figure, date: &NaiveDate, recorded_amount: &RecordedAmount) -> String {
+    let content = match recorded_amount {
+        RecordedAmount::NewAccount => format!("Created the account with {} on {}", figure, date),
+        RecordedAmount::Added => format!("Recorded {} on {}", figure, date),
+        RecordedAmount::AlreadyRecorded => format!("{} was already recorded on {}", figure, date),
+        RecordedAmount::Replaced(previous) => format!("Recorded {} on {}, instead of {}", figure, date, previous),
+    };
+    [
+        title(&format!("Account {}", name)),
+        content,
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_bucket_line_screen(name: &str, line: &str) -> String {
+    [
+        title(&format!("Bucket {}", name)),
+        format!("Added \"{}\"", line),
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_bucket_history_screen(name: &str, history: &[BucketHistoryRow]) -> String {
+    let content = if history.is_empty() {
+        "This bucket has no lines".to_string()
+    } else {
+        let mut table = Table::new();
+        table.set_header(vec!["Period", "Recommended deposit", "Actual deposit", "Withdrawal", "Total"]);
+
+        let optional_amount = |amount: &Option<Amount>| amount
+            .as_ref()
+            .map(|amount| amount.to_string())
+            .unwrap_or("-".to_string());
+
+        for row in history {
+            table.add_row(vec![
+                format!("{} to {}", row.period.start_date, row.period.end_date),
+                optional_amount(&row.recommended_deposit),
+                optional_amount(&row.actual_deposit),
+                optional_amount(&row.withdrawal),
+                row.total.to_string(),
+            ]);
+        }
+
+        table.to_string()
+    };
+
+    [
+        title(&format!("Bucket history: {}", name)),
+        content,
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_feasibility_screen(commitments: &[PeriodCommitment]) -> String {
+    let over_committed_periods = commitments
+        .iter()
+        .filter(|commitment| commitment.over_commitment().is_some())
+        .count();
+
+    let content = if commitments.is_empty() {
+        "No bucket has a target".to_string()
+    } else {
+        let mut table = Table::new();
+        table.set_header(vec!["Period", "Recommended deposits", "Predicted income", "Over-committed by"]);
+
+        for commitment in commitments {
+            table.add_row(vec![
+                format!("{} to {}", commitment.period.start_date, commitment.period.end_date),
+                commitment.recommended_deposits.to_string(),
+                commitment.predicted_income.to_string(),
+                commitment
+                    .over_commitment()
+                    .map(|amount| amount.to_string())
+                    .unwrap_or("-".to_string()),
+            ]);
+        }
+
+        let summary = if over_committed_periods == 0 {
+            "Every goal can be reached with the predicted income".to_string()
+        } else {
+            format!("The recommended deposits exceed the predicted income in {} period(s)", over_committed_periods)
+        };
+
+        format!("{}\n{}", table, summary)
+    };
+
+    [
+        title("Goal feasibility"),
+        content,
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_forecast_screen(until: &NaiveDate, current_balance: &Balance, projection: &[ProjectedPeriod]) -> String {
+    let mut table = Table::new();
+    table.set_header(vec!["Period", "Income", "Interest", "Expenses", "Bucket deposits", "Total", "Earmarked", "Free"]);
+
+    let balance_columns = |balance: &Balance| vec![
+        balance.total.to_string(),
+        balance.earmarked.to_string(),
+        balance.free().to_string(),
+    ];
+
+    let mut current_row = vec!["Current period".to_string(), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
+    current_row.extend(balance_columns(current_balance));
+    table.add_row(current_row);
+
+    for projected_period in projection {
+        let mut row = vec![
+            format!("{} to {}", projected_period.period.start_date, projected_period.period.end_date),
+            projected_period.income.to_string(),
+            projected_period.interest.to_string(),
+            projected_period.expenses.to_string(),
+            projected_period.deposits.to_string(),
+        ];
+        row.extend(balance_columns(&projected_period.balance));
+        table.add_row(row);
+    }
+
+    [
+        title(&format!("Forecast until {}", until)),
+        table.to_string(),
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+pub fn format_import_screen(account: &str, report: &ImportReport, candidates: &[IgnoredTransaction]) -> String {
+    let mut components = vec![
+        title(&format!("Import into {}", account)),
+        format!(
+            "{} balance(s) added, {} already recorded, {} conflicting",
+            report.added.len(),
+            report.already_recorded,
+            report.conflicts.len()
+        ),
+    ];
+
+    if !report.conflicts.is_empty() {
+        let mut table = Table::new();
+        table.set_header(vec!["Date", "Recorded", "Imported"]);
+        for conflict in report.conflicts.iter() {
+            table.add_row(vec![conflict.date.to_string(), conflict.recorded.to_string(), conflict.imported.to_string()]);
+        }
+        components.push(format!("{}\nThe conflicting balances were not imported", table));
+    }
+
+    if !candidates.is_empty() {
+        let lines: Vec<String> = candidates
+            .iter()
+            .map(|candidate| to_string(candidate).map_err(|error| error.to_string()))
+            .collect::<Result<_, _>>()
+            .unwrap_or_else(|error| vec![format!("Could not list the transactions: {}", error)]);
+        components.push(format!(
+            "Transactions of the statement, to copy into \"ignored_transactions\" if needed:\n{}",
+            lines.join("\n")
+        ));
+    }
+
+    components.push(format!("Release: {}", env!("RELEASE")));
+    components.join("\n\n")
+}
+
+/// Problems are listed as an error, one per line
+pub fn format_problems(problems: &[Problem]) -> String {
+    let lines: Vec<String> = problems.iter().map(|problem| format!("- {}", problem)).collect();
+    format!("{} problem(s) found\n{}\n", problems.len(), lines.join("\n"))
+}
+
+pub fn format_savings_projection_screen(from: &NaiveDate, until: &NaiveDate, projection: &[ProjectedInterest]) -> String {
+    let content = if projection.is_empty() {
+        "No interest will be paid".to_string()
+    } else {
+        let mut table = Table::new();
+        table.set_header(vec!["Date", "Account", "Balance", "Interest", "Accumulated interest"]);
+
+        for projected_interest in projection {
+            table.add_row(vec![
+                projected_interest.payment.date.to_string(),
+                projected_interest.account.clone(),
+                projected_interest.payment.balance.to_string(),
+                projected_interest.payment.interest.to_string(),
+                projected_interest.accumulated.to_string(),
+            ]);
+        }
+
+        table.to_string()
+    };
+
+    [
+        title(&format!("Savings interest : {} to {}", from, until)),
+        content,
+        format!("Release: {}", env!("RELEASE")),
+    ].join("\n\n")
+}
+
+fn format_group(group: &RemainingOperationScreenGroup) -> String {
+    let group_title = title(&group.name);
+    let content = if !group.empty() {
+        let mut table = Table::new();
+
+        let mut illustration_fields = vec![
+            String::from("Name"),
+            String::from("Amount")
+        ];
+        illustration_fields.extend(group.illustration_fields.clone());
+        table.set_header(illustration_fields);
+
+        for operand in group.operands.iter() {
+            let mut illustration_values = vec![
+                operand.name.clone(),
+                operand.amount.to_string()
+            ];
+
+            let raw_illustration_value = operand
+                .illustration
+                .clone()
+                .into_iter()
+                .map(|(_, value)| value)
+                .map(|illustration_value| match illustration_value {
+                    IllustrationValue::Amount(amount) => amount.to_string(),
+                    IllustrationValue::NullAmount | IllustrationValue::NullDate => "-".to_string(),
+                    IllustrationValue::Bool(bool) => (if bool { "✅" } else { "" }).into(),
+                    IllustrationValue::Date(date) => date.to_string(),
+                });
+
+            illustration_values.extend(raw_illustration_value);
+            table.add_row(illustration_values);
+        }
+        
+        let mut total_row = vec![
+            "Total".to_string(),
+            group.total.to_string()
+        ];
+        total_row.extend(group.illustration_fields.iter().map(|_| "".to_string()));
+        table.add_row(total_row);
+        
+        table.to_string()
+    } else {
+        "No operands for this period".to_string()
+    };
+    format!("{}\n{}", group_title, content)
+}
+
 fn title(string: &str) -> String {
     let string_length = string.len();
     string.to_string() + "\n" + &"=".repeat(string_length)
@@ -1,43 +1,322 @@
 use crate::vault::{VaultImpl, VaultReadable};
 use clap::Parser;
-use serde::Deserialize;
+use chrono::{Local, Months, NaiveDate};
 use std::env::current_dir;
-use std::fmt::Display;
-use argument_parsing::RemainingOptions;
-use crate::remaining_operation::core_types::RemainingOperation;
-use crate::amounts::exchange_rates::ExchangeRates;
+use std::process::exit;
+use std::fs::read_to_string;
+use argument_parsing::{AccountRecordOptions, AccountsCommand, AccountsOptions, BucketHistoryOptions, BucketLineOptions, BucketTargetOptions, BucketsCommand, BucketsOptions, Cli, Command, CsvImportOptions, ForecastOptions, GlobalOptions, ImportCommand, ImportOptions, OfxImportOptions, OutputFormat, PeriodsOptions, RemainingOptions, SavingsOptions};
+use crate::accounts::AccountGetter;
+use crate::accounts::recording::record_amount;
+use crate::accounts::savings::SavingsInterestGetter;
+use crate::buckets::BucketsVaultValue;
+use crate::check::check_vault;
+use crate::error::ChoError;
+use crate::buckets::recording::{record_line, NewLine};
+use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::remaining_operation::core_types::{GroupBuilder, OperandBuilder, RemainingOperation};
+use crate::remaining_operation::core_types::group::Group;
+use crate::amounts::exchange_rates::{ExchangeRates, ExchangeRatesVaultValue};
+use crate::amounts::currency_table::CurrencyTable;
+use crate::forecast::Forecast;
+use crate::goal_feasibility::check_goal_feasibility;
+use crate::import::csv::CsvProfilesVaultValue;
+use crate::import::ofx;
+use crate::import::{daily_closing_balances, import_statement};
+use crate::predicted_income::PredictedIncome;
 
 mod formatting;
 mod argument_parsing;
 mod tests;
 
-pub fn remaining_operation() {
-    let result: Result<String, String> = (|| {
-        let arguments = RemainingOptions::parse();
-        let vault_path = match &arguments.vault {
-            Some(a) => a.clone(),
-            None => current_dir().map_err(|e| e.to_string())?,
-        };
-        let vault = VaultImpl { path: vault_path };
-
-        let exchange_rates = ExchangeRates::from_indent_and_rates(arguments.exchange_rates)?;
-
-        let remaining_money = RemainingOperation::from_vault_values(
-            arguments.include_predicted_income,
-            &vault,
-            exchange_rates,
-        )?;
-
-        let screen = remaining_money.execute(
-            &arguments.target_currency,
-        )?;
-
-        Ok(formatting::format_remaining_operation_screen(&screen))
-    })();
-
-    if let Ok(screen) = result {
-        print!("{}", screen)
-    } else if let Err(error) = result {
-        println!("Could not compute remaining amount: {}", error)
+pub fn run() {
+    let cli = Cli::parse();
+
+    let (result, error_prefix) = match &cli.command {
+        Command::Remaining(options) => (remaining_operation(&cli.global, options), "Could not compute remaining amount"),
+        Command::Accounts(AccountsOptions { command: None }) => (accounts(&cli.global), "Could not display accounts"),
+        Command::Accounts(AccountsOptions { command: Some(AccountsCommand::Record(options)) }) => {
+            (account_record(&cli.global, options), "Could not record the amount")
+        }
+        Command::Buckets(BucketsOptions { command: None }) => (buckets(&cli.global), "Could not display buckets"),
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::History(options)) }) => {
+            (bucket_history(&cli.global, options), "Could not display bucket history")
+        }
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::Deposit(options)) }) => {
+            (bucket_line(&cli.global, options, NewLine::Deposit), "Could not record the deposit")
+        }
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::Withdraw(options)) }) => {
+            (bucket_line(&cli.global, options, NewLine::Withdrawal), "Could not record the withdrawal")
+        }
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::CancelDeposit(options)) }) => {
+            (bucket_line(&cli.global, options, NewLine::DepositCancellation), "Could not cancel the deposit")
+        }
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::CancelWithdrawal(options)) }) => {
+            (bucket_line(&cli.global, options, NewLine::WithdrawalCancellation), "Could not cancel the withdrawal")
+        }
+        Command::Buckets(BucketsOptions { command: Some(BucketsCommand::SetTarget(options)) }) => {
+            (bucket_target(&cli.global, options), "Could not set the target")
+        }
+        Command::Periods(options) => (periods(&cli.global, options), "Could not display periods"),
+        Command::Feasibility => (feasibility(&cli.global), "Could not check the goals"),
+        Command::Savings(options) => (savings(&cli.global, options), "Could not project the savings interest"),
+        Command::Forecast(options) => (forecast(&cli.global, options), "Could not forecast the accounts"),
+        Command::Import(ImportOptions { command: ImportCommand::Csv(options) }) => {
+            (import_csv(&cli.global, options), "Could not import the statement")
+        }
+        Command::Import(ImportOptions { command: ImportCommand::Ofx(options) }) => {
+            (import_ofx(&cli.global, options), "Could not import the statement")
+        }
+        Command::Check => (check(&cli.global), "Vault is invalid"),
+    };
+
+    match result {
+        Ok(screen) => print!("{}", screen),
+        Err(error) => {
+            eprintln!("{}: {}", error_prefix, error);
+            exit(error.exit_code());
+        }
+    }
+}
+
+fn open_vault(global: &GlobalOptions) -> Result<VaultImpl, ChoError> {
+    let vault_path = match &global.vault {
+        Some(a) => a.clone(),
+        None => current_dir().map_err(|e| e.to_string())?,
+    };
+    Ok(VaultImpl { path: vault_path })
+}
+
+fn exchange_rates(global: &GlobalOptions, vault: &VaultImpl) -> Result<ExchangeRates, ChoError> {
+    let currency_table = CurrencyTable::from_vault(vault)?;
+    let history = ExchangeRatesVaultValue::from_vault_optional(vault)?.unwrap_or_default();
+    ExchangeRates::from_history_and_rates(history, global.exchange_rates.clone(), &currency_table)
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+const LARGEST_TRANSACTIONS_PER_ACCOUNT: usize = 3;
+
+fn remaining_operation(global: &GlobalOptions, options: &RemainingOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let exchange_rates = exchange_rates(global, &vault)?;
+
+    let periods_configuration = PeriodConfigurationVaultValue::from_vault(&vault)?;
+    let today = today();
+    let accounts = AccountGetter::from_vault(&vault)?;
+
+    let largest_transactions = accounts.largest_transactions(
+        &periods_configuration.period_for_date(&today)?,
+        LARGEST_TRANSACTIONS_PER_ACCOUNT,
+        &exchange_rates,
+    )?;
+
+    let remaining_money = RemainingOperation::from_vault_values(
+        options.include_predicted_income,
+        &vault,
+        periods_configuration,
+        today,
+        accounts,
+        exchange_rates,
+    )?;
+
+    let screen = remaining_money.execute(
+        global.target_currency()?,
+    )?;
+
+    Ok(match options.format {
+        OutputFormat::Text => formatting::format_remaining_operation_screen(&screen, &largest_transactions),
+        OutputFormat::Json => formatting::format_remaining_operation_json(&screen, &largest_transactions)?,
+    })
+}
+
+/// Render a screen made of a single group, built the same way as the remaining screen builds it
+fn group_screen<O: OperandBuilder, B: GroupBuilder<O>>(
+    global: &GlobalOptions,
+    vault: &VaultImpl,
+    group_builder: B,
+) -> Result<String, ChoError> {
+    let period_configuration = &PeriodConfigurationVaultValue::from_vault(vault)?;
+    let exchange_rates = exchange_rates(global, vault)?;
+    let today = today();
+
+    let group = Group::from_group_builder(group_builder, &exchange_rates, period_configuration, &today)?
+        .into_remaining_operation_screen_group(&exchange_rates, global.target_currency()?)?;
+    let period = period_configuration.period_for_date(&today)?;
+
+    Ok(formatting::format_group_screen(&period, &group))
+}
+
+fn accounts(global: &GlobalOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    group_screen(global, &vault, AccountGetter::from_vault(&vault)?)
+}
+
+fn account_record(global: &GlobalOptions, options: &AccountRecordOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let date = options.date.unwrap_or(today());
+    let recorded_amount = record_amount(
+        &vault,
+        &options.name,
+        options.amount,
+        &date,
+        options.currency.as_deref(),
+        options.overwrite,
+    )?;
+
+    Ok(formatting::format_account_record_screen(&options.name, options.amount, &date, &recorded_amount))
+}
+
+fn buckets(global: &GlobalOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    group_screen(global, &vault, BucketsVaultValue::from_vault(&vault)?)
+}
+
+fn bucket_history(global: &GlobalOptions, options: &BucketHistoryOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let buckets = BucketsVaultValue::from_vault(&vault)?;
+    let bucket = buckets
+        .iter()
+        .find(|bucket| bucket.name() == options.name)
+        .ok_or(format!("No bucket is named \"{}\"", options.name))?;
+
+    let period_configuration = PeriodConfigurationVaultValue::from_vault(&vault)?;
+    let history = bucket.history(&period_configuration, &today(), &exchange_rates(global, &vault)?)?;
+
+    Ok(formatting::format_bucket_history_screen(bucket.name(), &history))
+}
+
+fn bucket_line(global: &GlobalOptions, options: &BucketLineOptions, new_line: NewLine) -> Result<String, ChoError> {
+    record_bucket_line(global, &options.name, new_line, &options.amount, options.date)
+}
+
+fn bucket_target(global: &GlobalOptions, options: &BucketTargetOptions) -> Result<String, ChoError> {
+    let new_line = NewLine::SetTarget { target_date: options.target_date };
+    record_bucket_line(global, &options.name, new_line, &options.amount, options.date)
+}
+
+fn record_bucket_line(
+    global: &GlobalOptions,
+    name: &str,
+    new_line: NewLine,
+    amount: &str,
+    date: Option<NaiveDate>,
+) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let today = today();
+    let line = record_line(
+        &vault,
+        name,
+        new_line,
+        amount,
+        &date.unwrap_or(today),
+        &today,
+        &exchange_rates(global, &vault)?,
+    )?;
+
+    Ok(formatting::format_bucket_line_screen(name, &line))
+}
+
+fn periods(global: &GlobalOptions, options: &PeriodsOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let period_configuration = PeriodConfigurationVaultValue::from_vault(&vault)?;
+
+    let mut periods = vec![period_configuration.period_for_date(&today())?];
+    for _ in 0..options.next {
+        let next_period_start = periods
+            .last()
+            .and_then(|period| period.end_date.succ_opt())
+            .ok_or("Could not compute the start of the next period")?;
+        periods.push(period_configuration.period_for_date(&next_period_start)?);
+    }
+
+    Ok(formatting::format_periods_screen(&periods))
+}
+
+fn feasibility(global: &GlobalOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+
+    let commitments = check_goal_feasibility(
+        &BucketsVaultValue::from_vault(&vault)?,
+        &PredictedIncome::from_vault(&vault)?,
+        &PeriodConfigurationVaultValue::from_vault(&vault)?,
+        &today(),
+        &exchange_rates(global, &vault)?,
+        global.target_currency()?,
+    )?;
+
+    Ok(formatting::format_feasibility_screen(&commitments))
+}
+
+fn savings(global: &GlobalOptions, options: &SavingsOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let today = today();
+    let until = today
+        .checked_add_months(Months::new(options.months))
+        .ok_or("Could not compute the end of the projection")?;
+
+    let projection = SavingsInterestGetter::from_vault(&vault)?.project(
+        &today,
+        &until,
+        &exchange_rates(global, &vault)?,
+        global.target_currency()?,
+    )?;
+
+    Ok(formatting::format_savings_projection_screen(&today, &until, &projection))
+}
+
+fn forecast(global: &GlobalOptions, options: &ForecastOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+
+    let (current_balance, projection) = Forecast::from_vault(&vault)?.project(
+        &PeriodConfigurationVaultValue::from_vault(&vault)?,
+        &today(),
+        &options.until,
+        &exchange_rates(global, &vault)?,
+        global.target_currency()?,
+    )?;
+
+    Ok(formatting::format_forecast_screen(&options.until, &current_balance, &projection))
+}
+
+fn import_csv(global: &GlobalOptions, options: &CsvImportOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let profiles = CsvProfilesVaultValue::from_vault(&vault)?;
+    let profile = profiles
+        .get(&options.profile)
+        .ok_or(format!("No CSV import profile is named \"{}\"", options.profile))?;
+
+    let content = read_to_string(&options.file)
+        .map_err(|error| format!("Could not read {}: {}", options.file.display(), error))?;
+    let lines = profile.read_statement(&content)?;
+    let report = import_statement(&vault, &options.account, |account| daily_closing_balances(account, &lines))?;
+
+    Ok(formatting::format_import_screen(&options.account, &report, &[]))
+}
+
+fn import_ofx(global: &GlobalOptions, options: &OfxImportOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+    let content = read_to_string(&options.file)
+        .map_err(|error| format!("Could not read {}: {}", options.file.display(), error))?;
+    let statement = ofx::parse(&content)?;
+
+    let mut candidates = vec![];
+    let report = import_statement(&vault, &options.account, |account| {
+        candidates = statement.ignored_transaction_candidates(account);
+        statement.daily_balances(account)
+    })?;
+
+    Ok(formatting::format_import_screen(&options.account, &report, &candidates))
+}
+
+fn check(global: &GlobalOptions) -> Result<String, ChoError> {
+    let vault = open_vault(global)?;
+
+    let problems = check_vault(&vault, exchange_rates(global, &vault));
+    if problems.is_empty() {
+        Ok("The vault is valid\n".to_string())
+    } else {
+        Err(formatting::format_problems(&problems).into())
     }
 }
@@ -3,7 +3,9 @@ mod format_remaining_operation_screen_tests {
     use std::collections::HashMap;
     use chrono::NaiveDate;
     use rust_decimal_macros::dec;
-    use crate::cli::formatting::format_remaining_operation_screen;
+    use crate::accounts::transactions::NotableTransaction;
+    use crate::cli::formatting::{format_remaining_operation_json, format_remaining_operation_screen};
+    use serde_json::json;
     use crate::period::Period;
     use pretty_assertions::assert_eq;
     use crate::amounts::{Amount, Currency};
@@ -77,7 +79,7 @@ mod format_remaining_operation_screen_tests {
                 },
             };
             
-            assert_eq!(format_remaining_operation_screen(&screen), self.expected_output)
+            assert_eq!(format_remaining_operation_screen(&screen, &[]), self.expected_output)
         }
     }
     
@@ -128,4 +130,188 @@ Remaining this period: €100
 Release: Development build"#.to_string(),
         }.test()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_largest_transactions() {
+        let exchange_rates = ExchangeRates::for_tests();
+        let screen = RemainingOperationScreen{
+            groups: vec![],
+            remaining: exchange_rates.euro("100"),
+            period: Period{
+                start_date: NaiveDate::from_ymd_opt(2025,1,1).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2025, 1, 31).unwrap()
+            },
+        };
+        let largest_transactions = vec![NotableTransaction {
+            account: "Current".into(),
+            date: NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(),
+            amount: exchange_rates.euro("-800"),
+            description: "Landlord - Rent".into(),
+        }];
+
+        assert_eq!(format_remaining_operation_screen(&screen, &largest_transactions), r#"Current period : 2025-01-01 to 2025-01-31
+=========================================
+
+Largest transactions
+====================
++---------+------------+--------+-----------------+
+| Account | Date       | Amount | Description     |
++=================================================+
+| Current | 2025-01-03 | €-800  | Landlord - Rent |
++---------+------------+--------+-----------------+
+
+Remaining this period: €100
+=============================
+
+Release: Development build"#.to_string())
+    }
+
+    #[test]
+    fn test_json() {
+        let exchange_rates = ExchangeRates::for_tests();
+        let group = Group::new("Normal group", vec![
+            make_operand(&exchange_rates, "Payment for cat".into(), false, true)
+        ]).expect("Could make group");
+        let screen = RemainingOperationScreen{
+            groups: vec![group.into_remaining_operation_screen_group(&exchange_rates, &"EUR".to_string()).expect("Could make group")],
+            remaining: exchange_rates.euro("100"),
+            period: Period{
+                start_date: NaiveDate::from_ymd_opt(2025,1,1).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2025, 1, 31).unwrap()
+            },
+        };
+        let largest_transactions = vec![NotableTransaction {
+            account: "Current".into(),
+            date: NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(),
+            amount: exchange_rates.euro("-800"),
+            description: "Landlord - Rent".into(),
+        }];
+
+        let output = format_remaining_operation_json(&screen, &largest_transactions).expect("Can write JSON");
+
+        assert!(output.ends_with("}\n"));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&output).unwrap(), json!({
+            "schema_version": 1,
+            "period": {"start_date": "2025-01-01", "end_date": "2025-01-31"},
+            "groups": [{
+                "name": "Normal group",
+                "illustration_fields": ["First amount", "Second amount", "Is enough", "Is luxury"],
+                "operands": [{
+                    "name": "Payment for cat",
+                    "amount": {"currency": "JPY", "figure": "5"},
+                    "illustration": {
+                        "First amount": {"currency": "JPY", "figure": "5"},
+                        "Second amount": null,
+                        "Is enough": true,
+                        "Is luxury": false
+                    }
+                }],
+                "total": {"currency": "EUR", "figure": "2.50"}
+            }],
+            "largest_transactions": [{
+                "account": "Current",
+                "date": "2025-01-03",
+                "amount": {"currency": "EUR", "figure": "-800"},
+                "description": "Landlord - Rent"
+            }],
+            "remaining": {"currency": "EUR", "figure": "100"}
+        }));
+    }
+}
+#[cfg(test)]
+mod argument_parsing_tests {
+    use std::path::PathBuf;
+    use chrono::NaiveDate;
+    use clap::Parser;
+    use rust_decimal_macros::dec;
+    use crate::cli::argument_parsing::{AccountsCommand, AccountsOptions, BucketsCommand, BucketsOptions, Cli, Command, OutputFormat};
+
+    #[test]
+    fn global_options_before_and_after_subcommand() {
+        let cli = Cli::try_parse_from([
+            "cho", "-V", "/tmp/vault", "remaining", "-p", "-t", "EUR", "-r", "JPY:2",
+        ]).expect("Can parse arguments");
+
+        assert_eq!(cli.global.vault, Some(PathBuf::from("/tmp/vault")));
+        assert_eq!(cli.global.target_currency, Some("EUR".to_string()));
+        assert_eq!(cli.global.exchange_rates, vec![("JPY".to_string(), dec!(2))]);
+        assert!(matches!(cli.command, Command::Remaining(options) if options.include_predicted_income));
+    }
+
+    #[test]
+    fn remaining_format() {
+        let cli = Cli::try_parse_from(["cho", "remaining"]).expect("Can parse arguments");
+        assert!(matches!(cli.command, Command::Remaining(options) if options.format == OutputFormat::Text));
+
+        let cli = Cli::try_parse_from(["cho", "remaining", "--format", "json"]).expect("Can parse arguments");
+        assert!(matches!(cli.command, Command::Remaining(options) if options.format == OutputFormat::Json));
+        assert!(Cli::try_parse_from(["cho", "remaining", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn subcommand_is_required() {
+        assert!(Cli::try_parse_from(["cho", "-t", "EUR"]).is_err());
+    }
+
+    #[test]
+    fn target_currency_is_optional_for_periods() {
+        let cli = Cli::try_parse_from(["cho", "periods"]).expect("Can parse arguments");
+
+        assert!(cli.global.target_currency().is_err());
+        assert!(matches!(cli.command, Command::Periods(options) if options.next == 3));
+    }
+
+    #[test]
+    fn buckets_with_and_without_history() {
+        let cli = Cli::try_parse_from(["cho", "buckets"]).expect("Can parse arguments");
+        assert!(matches!(cli.command, Command::Buckets(BucketsOptions { command: None })));
+
+        let cli = Cli::try_parse_from(["cho", "buckets", "history", "Holidays"]).expect("Can parse arguments");
+        assert!(matches!(
+            cli.command,
+            Command::Buckets(BucketsOptions { command: Some(BucketsCommand::History(options)) }) if options.name == "Holidays"
+        ));
+    }
+
+    #[test]
+    fn account_record() {
+        let cli = Cli::try_parse_from(["cho", "accounts"]).expect("Can parse arguments");
+        assert!(matches!(cli.command, Command::Accounts(AccountsOptions { command: None })));
+
+        let cli = Cli::try_parse_from(["cho", "accounts", "record", "Card", "-120.50", "--currency", "EUR"])
+            .expect("Can parse arguments");
+        assert!(matches!(
+            cli.command,
+            Command::Accounts(AccountsOptions { command: Some(AccountsCommand::Record(options)) })
+                if options.amount == dec!(-120.50) && options.currency.as_deref() == Some("EUR") && !options.overwrite
+        ));
+    }
+
+    #[test]
+    fn check() {
+        for command in ["check", "validate"] {
+            let cli = Cli::try_parse_from(["cho", command]).expect("Can parse arguments");
+            assert!(matches!(cli.command, Command::Check));
+        }
+    }
+
+    #[test]
+    fn bucket_lines() {
+        let cli = Cli::try_parse_from(["cho", "buckets", "deposit", "Holidays", "¥150", "--date", "2025-09-01"])
+            .expect("Can parse arguments");
+        assert!(matches!(
+            cli.command,
+            Command::Buckets(BucketsOptions { command: Some(BucketsCommand::Deposit(options)) })
+                if options.name == "Holidays" && options.amount == "¥150" && options.date == NaiveDate::from_ymd_opt(2025, 9, 1)
+        ));
+
+        let cli = Cli::try_parse_from(["cho", "buckets", "set-target", "Holidays", "1000", "2026-06-30"])
+            .expect("Can parse arguments");
+        assert!(matches!(
+            cli.command,
+            Command::Buckets(BucketsOptions { command: Some(BucketsCommand::SetTarget(options)) })
+                if options.target_date == NaiveDate::from_ymd_opt(2026, 6, 30).unwrap() && options.date.is_none()
+        ));
+        assert!(Cli::try_parse_from(["cho", "buckets", "cancel-withdrawal", "Holidays", "10"]).is_ok());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::amounts::CurrencyIdent;
+use crate::amounts::currency_table::CustomCurrenciesVaultValue;
+use crate::vault::VaultReadable;
+use chrono::NaiveDate;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// The errors callers need to tell apart: the vault cannot be read, a currency is unknown, a date is outside the
+/// periods. Functions that have not moved to it yet return their errors as strings, which convert from and into
+/// `ChoError::Other`.
+#[derive(Debug)]
+pub enum ChoError {
+    /// A file of the vault could not be read or written
+    File { path: PathBuf, source: std::io::Error },
+    /// The content of a file or the value of a key does not have the expected format
+    Json(serde_json::Error),
+    /// The key is not in the configuration file
+    MissingKey(String),
+    UnknownCurrency(CurrencyIdent),
+    /// The code is neither an ISO 4217 code nor a custom currency of the vault
+    UnsupportedCurrency(CurrencyIdent),
+    /// No currency uses the sign, or several of them do
+    UnknownCurrencySign { sign: String, matching: Vec<CurrencyIdent> },
+    /// The periods configuration starts after the date
+    DateBeforePeriods(NaiveDate),
+    /// What was being done when the error happened, eg. the key being read, the bucket and line being parsed
+    Context { context: String, source: Box<ChoError> },
+    Other(String),
+}
+
+impl ChoError {
+    /// The exit code of the command failing with this error. 2 is left to argument parsing errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ChoError::Context { source, .. } => source.exit_code(),
+            ChoError::Other(_) => 1,
+            ChoError::File { .. } => 3,
+            ChoError::Json(_) | ChoError::MissingKey(_) => 4,
+            ChoError::UnknownCurrency(_) | ChoError::UnsupportedCurrency(_) | ChoError::UnknownCurrencySign { .. } => 5,
+            ChoError::DateBeforePeriods(_) => 6,
+        }
+    }
+}
+
+impl Display for ChoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChoError::File { path, source } => write!(f, "{}: {}", path.display(), source),
+            ChoError::Json(error) => write!(f, "{}", error),
+            ChoError::MissingKey(key) => write!(f, "Could not find key: {}", key),
+            ChoError::UnknownCurrency(ident) => write!(f, "Could not find currency ident: {}", ident),
+            ChoError::UnsupportedCurrency(code) => write!(
+                f,
+                "Unsupported currency: {}. It is not an ISO 4217 code and is not defined under the \"{}\" key of the vault.",
+                code,
+                CustomCurrenciesVaultValue::KEY
+            ),
+            ChoError::UnknownCurrencySign { sign, matching } if matching.is_empty() => {
+                write!(f, "Could not find currency with sign: {}", sign)
+            }
+            ChoError::UnknownCurrencySign { sign, matching } => {
+                write!(f, "Several currencies use the sign {}: {}", sign, matching.join(", "))
+            }
+            ChoError::DateBeforePeriods(_) => write!(f, "Date is before PeriodsConfiguration's start"),
+            ChoError::Context { context, source } => write!(f, "{}: {}", context, source),
+            ChoError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ChoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChoError::File { source, .. } => Some(source),
+            ChoError::Json(error) => Some(error),
+            ChoError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Tests compare errors by what they say, io and serde errors cannot be compared otherwise
+#[cfg(test)]
+impl PartialEq for ChoError {
+    fn eq(&self, other: &ChoError) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl From<String> for ChoError {
+    fn from(message: String) -> ChoError {
+        ChoError::Other(message)
+    }
+}
+
+impl From<&str> for ChoError {
+    fn from(message: &str) -> ChoError {
+        ChoError::Other(message.to_string())
+    }
+}
+
+impl From<ChoError> for String {
+    fn from(error: ChoError) -> String {
+        error.to_string()
+    }
+}
+
+impl From<serde_json::Error> for ChoError {
+    fn from(error: serde_json::Error) -> ChoError {
+        ChoError::Json(error)
+    }
+}
+
+/// Says what was being done when an error happened, keeping the error as the source
+pub trait Context<T> {
+    fn context<C: Display>(self, context: impl FnOnce() -> C) -> Result<T, ChoError>;
+}
+
+impl<T, E: Into<ChoError>> Context<T> for Result<T, E> {
+    fn context<C: Display>(self, context: impl FnOnce() -> C) -> Result<T, ChoError> {
+        self.map_err(|error| ChoError::Context { context: context().to_string(), source: Box::new(error.into()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn context_keeps_the_source() {
+        let result: Result<(), ChoError> = Err(ChoError::UnknownCurrency("GBP".to_string()));
+        let error = result.context(|| "Bucket \"Car\", line 3").context(|| "Could not read the buckets").unwrap_err();
+
+        assert_eq!(error.to_string(), "Could not read the buckets: Bucket \"Car\", line 3: Could not find currency ident: GBP");
+        assert_eq!(error.exit_code(), 5);
+        assert_eq!(
+            error.source().and_then(|source| source.source()).map(|source| source.to_string()),
+            Some("Could not find currency ident: GBP".to_string())
+        );
+        assert_eq!(ChoError::from("Oops".to_string()).exit_code(), 1);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::accounts::savings::SavingsInterestGetter;
+use crate::accounts::AccountGetter;
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::{Add, Amount, CurrencyIdent, Minus};
+use crate::buckets::{Bucket, BucketsVaultValue};
+use crate::error::ChoError;
+use crate::period::{Period, PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::predicted_income::PredictedIncome;
+use crate::recurring_expense::RecurringExpensesVaultValue;
+use crate::vault::{Vault, VaultReadable};
+use chrono::NaiveDate;
+
+/// The money held in the accounts, and the part of it set aside in the buckets
+#[derive(Debug, Eq, PartialEq)]
+pub struct Balance {
+    pub total: Amount,
+    pub earmarked: Amount,
+}
+
+impl Balance {
+    /// What is not set aside in any bucket
+    pub fn free(&self) -> Amount {
+        self.total.minus(&self.earmarked)
+    }
+}
+
+/// How the balance evolves during a period following the current one
+#[derive(Debug, Eq, PartialEq)]
+pub struct ProjectedPeriod {
+    pub period: Period,
+    pub income: Amount,
+    pub interest: Amount,
+    pub expenses: Amount,
+    /// Moved from the free money to the buckets
+    pub deposits: Amount,
+    /// The balance at the end of the period
+    pub balance: Balance,
+}
+
+pub struct Forecast {
+    accounts: AccountGetter,
+    buckets: Vec<Bucket>,
+    predicted_income: PredictedIncome,
+    recurring_expenses: RecurringExpensesVaultValue,
+}
+
+impl Forecast {
+    pub fn from_vault<V: Vault>(vault: &V) -> Result<Forecast, ChoError> {
+        Ok(Forecast {
+            accounts: AccountGetter::from_vault(vault)?,
+            buckets: BucketsVaultValue::from_vault(vault)?,
+            predicted_income: PredictedIncome::from_vault(vault)?,
+            recurring_expenses: RecurringExpensesVaultValue::from_vault_optional(vault)?.unwrap_or_default(),
+        })
+    }
+
+    /// The balance by the end of the current period, then one projected period per following period until
+    /// the one containing `until`. Every deposit the buckets recommend is assumed to be made, starting with
+    /// the ones still missing this period.
+    ///
+    /// The accounts show the balance of today, what comes after it this period is projected on top of it. The salary
+    /// has no payment date, so the salary of the current period is counted as still to come, as it is by the
+    /// remaining screen with the predicted income.
+    pub fn project(
+        &self,
+        period_config: &PeriodConfigurationVaultValue,
+        today: &NaiveDate,
+        until: &NaiveDate,
+        exchange_rates: &ExchangeRates,
+        target_currency: &CurrencyIdent,
+    ) -> Result<(Balance, Vec<ProjectedPeriod>), ChoError> {
+        if until < today {
+            return Err(format!("The end of the forecast ({}) is before today ({})", until, today).into());
+        }
+
+        let zero = exchange_rates.zero(target_currency)?;
+        let mut projected_deposits = vec![];
+        let mut earmarked = zero.clone();
+        for bucket in self.buckets.iter() {
+            let in_bucket = |error| format!("Bucket \"{}\": {}", bucket.name(), error);
+            earmarked = earmarked.add(&bucket.earmarked_at_period_end(period_config, today, exchange_rates).map_err(in_bucket)?);
+            projected_deposits.extend(
+                bucket
+                    .projected_recommended_deposits(period_config, today, exchange_rates)
+                    .map_err(in_bucket)?,
+            );
+        }
+        let savings = SavingsInterestGetter::from_accounts(&self.accounts);
+        let mut total = self.accounts.total_at(today, exchange_rates, target_currency)?;
+        let mut period_end = period_config.period_for_date(today)?.end_date;
+        if let Some(tomorrow) = today.succ_opt().filter(|tomorrow| *tomorrow <= period_end) {
+            let rest_of_period = Period { start_date: tomorrow, end_date: period_end };
+            let (income, interest, expenses) =
+                self.movements(&rest_of_period, &savings, exchange_rates, target_currency)?;
+            total = total.add(&income).add(&interest).minus(&expenses);
+        }
+        let current_balance = Balance { total, earmarked };
+
+        let mut projection: Vec<ProjectedPeriod> = vec![];
+        while period_end < *until {
+            let next_period_start = period_end
+                .succ_opt()
+                .ok_or("Could not compute the start of the next period")?;
+            let period = period_config.period_for_date(&next_period_start)?;
+            period_end = period.end_date;
+
+            let (income, interest, expenses) = self.movements(&period, &savings, exchange_rates, target_currency)?;
+            let deposits = projected_deposits
+                .iter()
+                .filter(|(deposit_period, _)| *deposit_period == period)
+                .fold(zero.clone(), |total, (_, deposit)| total.add(deposit));
+
+            let previous_balance = projection.last().map_or(&current_balance, |projected| &projected.balance);
+            let balance = Balance {
+                total: previous_balance.total.add(&income).add(&interest).minus(&expenses),
+                earmarked: previous_balance.earmarked.add(&deposits),
+            };
+            projection.push(ProjectedPeriod { period, income, interest, expenses, deposits, balance });
+        }
+
+        Ok((current_balance, projection))
+    }
+
+    /// The income, interest and expenses expected between the start and the end of the period
+    fn movements(
+        &self,
+        period: &Period,
+        savings: &SavingsInterestGetter,
+        exchange_rates: &ExchangeRates,
+        target_currency: &CurrencyIdent,
+    ) -> Result<(Amount, Amount, Amount), ChoError> {
+        let zero = exchange_rates.zero(target_currency)?;
+        let income = zero.add(&self.predicted_income.for_period(period, exchange_rates)?);
+        let interest = savings
+            .project(&period.start_date, &period.end_date, exchange_rates, target_currency)?
+            .last()
+            .map_or(zero.clone(), |projected_interest| projected_interest.accumulated.clone());
+        let expenses = self
+            .recurring_expenses
+            .iter()
+            .try_fold(zero.clone(), |total, expense| Ok::<Amount, String>(total.add(&expense.for_period(period, exchange_rates)?)))?;
+        Ok((income, interest, expenses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::AccountJson;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    fn forecast() -> Forecast {
+        let savings: AccountJson = serde_json::from_value(json!({
+            "name": "Savings",
+            "currency": "EUR",
+            "amounts": [{"date": "2025-01-01", "amount": 12000}],
+            "savings": {"annual_rate_percent": "1.5", "schedule": "quarterly"}
+        })).expect("Can parse account");
+
+        Forecast {
+            accounts: AccountGetter::new(vec![
+                AccountJson::new("Current".to_string(), "JPY".to_string(), vec![(mkdate(9, 1), dec!(4000))]),
+                savings,
+            ]),
+            buckets: serde_json::from_value(json!([{
+                "name": "Holidays",
+                "currency": "JPY",
+                "lines": [
+                    "2025/09/01 TARG ¥9000 2025/11/30",
+                    "2025/09/03 DEPO ¥1000",
+                ]
+            }])).expect("Can parse buckets"),
+            predicted_income: serde_json::from_value(json!({"currency": "JPY", "figure": "2000"}))
+                .expect("Can parse income"),
+            recurring_expenses: serde_json::from_value(json!([{
+                "name": "Rent",
+                "currency": "JPY",
+                "amount": "600",
+                "frequency": {"monthly": {"day": 25}}
+            }])).expect("Can parse recurring expenses"),
+        }
+    }
+
+    #[test]
+    fn project() {
+        let ex = ExchangeRates::for_tests();
+        let (current_balance, projection) = forecast().project(
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &mkdate(9, 15),
+            &mkdate(12, 1),
+            &ex,
+            &"EUR".to_string(),
+        ).expect("Can project");
+
+        // Today is in the middle of September: the salary (€1000), the rent of the 25th (€300) and the interest of
+        // the 30th (€45) are still to come this period
+        // The holidays need ¥3000 (€1500) each period, ¥1000 of which were deposited this period
+        assert_eq!(current_balance, Balance { total: ex.euro("14745"), earmarked: ex.euro("1500") });
+        assert_eq!(current_balance.free(), ex.euro("13245"));
+
+        let period = |month: u32, last_day: u32| Period { start_date: mkdate(month, 1), end_date: mkdate(month, last_day) };
+        assert_eq!(projection, vec![
+            ProjectedPeriod {
+                period: period(10, 31),
+                income: ex.euro("1000"),
+                interest: ex.euro("0"),
+                expenses: ex.euro("300"),
+                deposits: ex.euro("1500"),
+                balance: Balance { total: ex.euro("15445"), earmarked: ex.euro("3000") },
+            },
+            ProjectedPeriod {
+                period: period(11, 30),
+                income: ex.euro("1000"),
+                interest: ex.euro("0"),
+                expenses: ex.euro("300"),
+                deposits: ex.euro("1500"),
+                balance: Balance { total: ex.euro("16145"), earmarked: ex.euro("4500") },
+            },
+            ProjectedPeriod {
+                period: period(12, 31),
+                income: ex.euro("1000"),
+                interest: ex.euro("45"),
+                expenses: ex.euro("300"),
+                deposits: ex.euro("0"),
+                balance: Balance { total: ex.euro("16890"), earmarked: ex.euro("4500") },
+            },
+        ]);
+    }
+
+    #[test]
+    fn last_day_of_the_period() {
+        let ex = ExchangeRates::for_tests();
+        let (current_balance, _) = forecast().project(
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &mkdate(9, 30),
+            &mkdate(9, 30),
+            &ex,
+            &"EUR".to_string(),
+        ).expect("Can project");
+
+        // Nothing is left to come this period, the accounts already show it
+        assert_eq!(current_balance.total, ex.euro("14000"));
+    }
+
+    #[test]
+    fn until_the_current_period() {
+        let ex = ExchangeRates::for_tests();
+        let project = |until: NaiveDate| forecast().project(
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &mkdate(9, 15),
+            &until,
+            &ex,
+            &"EUR".to_string(),
+        ).map(|(_, projection)| projection);
+
+        assert_eq!(project(mkdate(9, 30)), Ok(vec![]));
+        assert_eq!(
+            project(mkdate(9, 14)),
+            Err("The end of the forecast (2025-09-14) is before today (2025-09-15)".into())
+        );
+    }
+}
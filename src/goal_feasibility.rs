@@ -0,0 +1,138 @@
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::{Add, Amount, CurrencyIdent, Minus};
+use crate::buckets::Bucket;
+use crate::error::{ChoError, Context};
+use crate::period::{Period, PeriodConfigurationVaultValue};
+use crate::predicted_income::PredictedIncome;
+use chrono::NaiveDate;
+
+/// What the buckets ask to be deposited during a period, compared to the income predicted for it
+#[derive(Debug, Eq, PartialEq)]
+pub struct PeriodCommitment {
+    pub period: Period,
+    pub recommended_deposits: Amount,
+    pub predicted_income: Amount,
+}
+
+impl PeriodCommitment {
+    /// How much the recommended deposits exceed the predicted income, None when the income covers them
+    pub fn over_commitment(&self) -> Option<Amount> {
+        let over_commitment = self.predicted_income.minus(&self.recommended_deposits);
+        over_commitment.is_negative().then(|| over_commitment.flip_sign())
+    }
+}
+
+/// Sum the deposits recommended by every bucket for each period, from the current one to the one of the
+/// last target date, and compare them to the predicted income
+pub fn check_goal_feasibility(
+    buckets: &[Bucket],
+    predicted_income: &PredictedIncome,
+    period_config: &PeriodConfigurationVaultValue,
+    today: &NaiveDate,
+    exchange_rates: &ExchangeRates,
+    target_currency: &CurrencyIdent,
+) -> Result<Vec<PeriodCommitment>, ChoError> {
+    let mut commitments: Vec<PeriodCommitment> = vec![];
+
+    for bucket in buckets {
+        let deposits = bucket
+            .projected_recommended_deposits(period_config, today, exchange_rates)
+            .context(|| format!("Bucket \"{}\"", bucket.name()))?;
+
+        // Every projection starts with the current period, the nth deposit of each bucket is for the same period
+        for (index, (period, deposit)) in deposits.into_iter().enumerate() {
+            if index == commitments.len() {
+                commitments.push(PeriodCommitment {
+                    predicted_income: exchange_rates
+                        .zero(target_currency)?
+                        .add(&predicted_income.for_period(&period, exchange_rates)?),
+                    recommended_deposits: exchange_rates.zero(target_currency)?,
+                    period,
+                });
+            }
+            let commitment = &mut commitments[index];
+            commitment.recommended_deposits = commitment.recommended_deposits.add(&deposit);
+        }
+    }
+
+    Ok(commitments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn mkdate(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).expect("Can create date")
+    }
+
+    #[test]
+    fn over_committed_periods() {
+        let ex = ExchangeRates::for_tests();
+        let buckets: Vec<Bucket> = serde_json::from_value(json!([
+            {
+                "name": "Holidays",
+                "currency": "JPY",
+                "lines": [
+                    "2025/09/01 TARG ¥90000 2025/11/30",
+                    "2025/09/03 DEPO ¥10000",
+                ]
+            },
+            {
+                "name": "Computer",
+                "currency": "EUR",
+                "lines": ["2025/08/01 TARG €100 2025/09/30"]
+            }
+        ])).expect("Can parse buckets");
+        let predicted_income: PredictedIncome =
+            serde_json::from_value(json!({"currency": "JPY", "figure": "30100"})).expect("Can parse income");
+
+        let commitments = check_goal_feasibility(
+            &buckets,
+            &predicted_income,
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &mkdate(9, 15),
+            &ex,
+            &"EUR".to_string(),
+        ).expect("Can check the goals");
+
+        let period = |month: u32, last_day: u32| Period { start_date: mkdate(month, 1), end_date: mkdate(month, last_day) };
+        assert_eq!(commitments, vec![
+            // The holidays need ¥30000 (€15000) each period, the computer needs €100 this period
+            PeriodCommitment {
+                period: period(9, 30),
+                recommended_deposits: ex.euro("15100"),
+                predicted_income: ex.euro("15050"),
+            },
+            PeriodCommitment {
+                period: period(10, 31),
+                recommended_deposits: ex.euro("15000"),
+                predicted_income: ex.euro("15050"),
+            },
+            PeriodCommitment {
+                period: period(11, 30),
+                recommended_deposits: ex.euro("15000"),
+                predicted_income: ex.euro("15050"),
+            },
+        ]);
+        assert_eq!(
+            commitments.iter().map(PeriodCommitment::over_commitment).collect::<Vec<_>>(),
+            vec![Some(ex.euro("50")), None, None]
+        );
+    }
+
+    #[test]
+    fn over_commitment() {
+        let ex = ExchangeRates::for_tests();
+        let commitment = PeriodCommitment {
+            period: Period { start_date: mkdate(9, 1), end_date: mkdate(9, 30) },
+            recommended_deposits: ex.euro("150"),
+            predicted_income: ex.yen("200"),
+        };
+
+        assert_eq!(commitment.over_commitment(), Some(ex.yen("100")));
+    }
+}
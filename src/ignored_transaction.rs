@@ -3,9 +3,10 @@ use chrono::NaiveDate;
 use derive_builder::Builder;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use crate::period::{PeriodConfigurationVaultValue, PeriodsConfiguration};
 use crate::amounts::exchange_rates::ExchangeRates;
+use crate::error::ChoError;
 use crate::remaining_operation::core_types::{GroupBuilder, IllustrationValue, Operand, OperandBuilder};
 use crate::remaining_operation::core_types::group::Group;
 
@@ -13,7 +14,7 @@ pub type Figure = Decimal;
 pub type Currency = String;
 
 #[cfg_attr(test, derive(Clone, Builder))]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct IgnoredTransaction {
     name: String,
     currency: Currency,
@@ -21,9 +22,15 @@ pub struct IgnoredTransaction {
     date: NaiveDate,
 }
 
+impl IgnoredTransaction {
+    pub fn new(name: String, currency: Currency, amount: Figure, date: NaiveDate) -> IgnoredTransaction {
+        IgnoredTransaction { name, currency, amount, date }
+    }
+}
+
 // TODO requires tests!!
 impl OperandBuilder for IgnoredTransaction {
-    fn build(self, period_configuration: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, String> {
+    fn build(self, period_configuration: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
         let current_period = period_configuration.period_for_date(today)?;
         if !current_period.contains(&self.date) {
            return Ok(None);
@@ -52,7 +59,7 @@ impl VaultReadable for IgnoredTransactionsVaultValues {
 }
 
 impl GroupBuilder<IgnoredTransaction> for IgnoredTransactionsVaultValues {
-    fn build(self) -> Result<(String, Vec<IgnoredTransaction>), String> {
+    fn build(self) -> Result<(String, Vec<IgnoredTransaction>), ChoError> {
         Ok(("Ignored Transactions".into(), self.into_iter().collect()))
     }
 }
@@ -0,0 +1,162 @@
+use crate::accounts::{AccountGetter, AccountJson, Figure, QueriableAccount};
+use crate::accounts::recording::write_amounts;
+use crate::error::{ChoError, Context};
+use crate::vault::Vault;
+use chrono::NaiveDate;
+
+pub mod csv;
+pub mod ofx;
+
+/// The balance of an account at the end of a day
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DailyBalance {
+    pub date: NaiveDate,
+    pub figure: Figure,
+}
+
+/// A balance of the statement that differs from the one already recorded for the same day
+#[derive(Debug, Eq, PartialEq)]
+pub struct ConflictingBalance {
+    pub date: NaiveDate,
+    pub recorded: Figure,
+    pub imported: Figure,
+}
+
+#[derive(Debug, Eq, PartialEq, Default)]
+pub struct ImportReport {
+    pub added: Vec<DailyBalance>,
+    pub already_recorded: usize,
+    /// Left as they are in the account
+    pub conflicts: Vec<ConflictingBalance>,
+}
+
+/// A line of a statement, in chronological order. Statements give either the balance after each line or
+/// only the amount of each line.
+pub enum StatementLine {
+    Balance(NaiveDate, Figure),
+    Amount(NaiveDate, Figure),
+}
+
+/// The balance at the end of each day of the statement. When the statement only gives amounts, they are added to
+/// the balance recorded in the account before the first of them.
+pub fn daily_closing_balances(account: &AccountJson, lines: &[StatementLine]) -> Result<Vec<DailyBalance>, ChoError> {
+    let mut balances: Vec<DailyBalance> = vec![];
+    for line in lines {
+        let (date, figure) = match line {
+            StatementLine::Balance(date, balance) => (*date, *balance),
+            StatementLine::Amount(date, amount) => {
+                let previous_balance = match balances.last() {
+                    Some(balance) => balance.figure,
+                    None => {
+                        let day_before = date.pred_opt().ok_or("Could not compute the day before the statement")?;
+                        account
+                            .amount_at(&day_before)
+                            .context(|| format!("Could not find the balance before the statement starts on {}", date))?
+                            .figure
+                    }
+                };
+                (*date, previous_balance + amount)
+            }
+        };
+
+        match balances.last_mut() {
+            Some(balance) if balance.date == date => balance.figure = figure,
+            Some(balance) if balance.date > date => {
+                return Err(format!("The statement is not in chronological order: {} comes after {}", date, balance.date).into())
+            }
+            _ => balances.push(DailyBalance { date, figure }),
+        }
+    }
+    Ok(balances)
+}
+
+/// Sort the balances into the ones to add to the account, the ones it already has and the ones that conflict with it
+pub fn compare_with_account(account: &AccountJson, balances: Vec<DailyBalance>) -> ImportReport {
+    let mut report = ImportReport::default();
+    for balance in balances {
+        match account.recorded_amount(&balance.date) {
+            None => report.added.push(balance),
+            Some(recorded) if recorded == balance.figure => report.already_recorded += 1,
+            Some(recorded) => report.conflicts.push(ConflictingBalance {
+                date: balance.date,
+                recorded,
+                imported: balance.figure,
+            }),
+        }
+    }
+    report
+}
+
+/// Merge the balances computed from a statement into the account, without touching the days it already has
+pub fn import_statement<V: Vault>(
+    vault: &V,
+    account_name: &str,
+    to_daily_balances: impl FnOnce(&AccountJson) -> Result<Vec<DailyBalance>, ChoError>,
+) -> Result<ImportReport, ChoError> {
+    let (path, account) = AccountGetter::find_account_file(vault, account_name)?;
+    let report = compare_with_account(&account, to_daily_balances(&account)?);
+    if !report.added.is_empty() {
+        let amounts: Vec<(NaiveDate, Figure)> = report.added.iter().map(|balance| (balance.date, balance.figure)).collect();
+        write_amounts(&path, &amounts)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    fn mkdate(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).expect("Can create date")
+    }
+
+    fn account() -> AccountJson {
+        AccountJson::new("Current".to_string(), "EUR".to_string(), vec![(mkdate(1), dec!(1000)), (mkdate(5), dec!(800))])
+    }
+
+    #[test]
+    fn closing_balances() {
+        assert_eq!(
+            daily_closing_balances(&account(), &[
+                StatementLine::Balance(mkdate(5), dec!(800)),
+                StatementLine::Balance(mkdate(5), dec!(750)),
+                StatementLine::Balance(mkdate(7), dec!(700)),
+            ]),
+            Ok(vec![DailyBalance { date: mkdate(5), figure: dec!(750) }, DailyBalance { date: mkdate(7), figure: dec!(700) }])
+        );
+
+        // The statement starts from the balance recorded on the 5th
+        assert_eq!(
+            daily_closing_balances(&account(), &[
+                StatementLine::Amount(mkdate(6), dec!(-20.5)),
+                StatementLine::Amount(mkdate(6), dec!(-9.5)),
+                StatementLine::Amount(mkdate(8), dec!(100)),
+            ]),
+            Ok(vec![DailyBalance { date: mkdate(6), figure: dec!(770) }, DailyBalance { date: mkdate(8), figure: dec!(870) }])
+        );
+
+        assert_eq!(
+            daily_closing_balances(&account(), &[StatementLine::Amount(mkdate(1), dec!(10))]),
+            Err("Could not find the balance before the statement starts on 2025-09-01: \
+                The requested date is before the start of the amount history".into())
+        );
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(
+            compare_with_account(&account(), vec![
+                DailyBalance { date: mkdate(1), figure: dec!(1000) },
+                DailyBalance { date: mkdate(3), figure: dec!(900) },
+                DailyBalance { date: mkdate(5), figure: dec!(810) },
+            ]),
+            ImportReport {
+                added: vec![DailyBalance { date: mkdate(3), figure: dec!(900) }],
+                already_recorded: 1,
+                conflicts: vec![ConflictingBalance { date: mkdate(5), recorded: dec!(800), imported: dec!(810) }],
+            }
+        );
+    }
+}
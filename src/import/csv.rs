@@ -0,0 +1,180 @@
+use super::StatementLine;
+use crate::accounts::Figure;
+use crate::vault::VaultReadable;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// How to read the CSV export of a bank. Columns are named after the header of the file.
+#[derive(Deserialize)]
+pub struct CsvProfile {
+    date_column: String,
+    /// chrono format, eg. "%d/%m/%Y"
+    #[serde(default = "default_date_format")]
+    date_format: String,
+    #[serde(default = "default_decimal_separator")]
+    decimal_separator: char,
+    #[serde(default = "default_delimiter")]
+    delimiter: char,
+    /// The amount of each line, used when there is no balance column
+    amount_column: Option<String>,
+    /// The balance of the account after each line
+    balance_column: Option<String>,
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_decimal_separator() -> char {
+    '.'
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+pub type CsvProfilesVaultValue = HashMap<String, CsvProfile>;
+impl VaultReadable for CsvProfilesVaultValue {
+    const KEY: &'static str = "csv_import_profiles";
+}
+
+impl CsvProfile {
+    /// The lines of the statement, in chronological order. Exports that start with the most recent line are reversed.
+    pub fn read_statement(&self, content: &str) -> Result<Vec<StatementLine>, String> {
+        let delimiter = u8::try_from(self.delimiter).map_err(|_| format!("Unsupported delimiter {}", self.delimiter))?;
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_reader(content.as_bytes());
+
+        let headers = reader.headers().map_err(|error| format!("Could not read the header: {}", error))?.clone();
+        let column = |name: &String| {
+            headers
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or(format!("The file has no column named \"{}\"", name))
+        };
+        let date_column = column(&self.date_column)?;
+        let (figure_column, is_balance) = match (&self.balance_column, &self.amount_column) {
+            (Some(balance_column), _) => (column(balance_column)?, true),
+            (None, Some(amount_column)) => (column(amount_column)?, false),
+            (None, None) => return Err("The profile needs a balance column or an amount column".to_string()),
+        };
+
+        let mut lines = vec![];
+        for (index, record) in reader.records().enumerate() {
+            // The header is the first line of the file
+            let line_number = index + 2;
+            let record = record.map_err(|error| format!("Line {}: {}", line_number, error))?;
+            let cell = |column: usize| record.get(column).map(str::trim).unwrap_or("");
+
+            // Banks leave the balance empty on some lines, and add lines without a date for totals
+            if cell(date_column).is_empty() || cell(figure_column).is_empty() {
+                continue;
+            }
+
+            let date = NaiveDate::parse_from_str(cell(date_column), &self.date_format)
+                .map_err(|error| format!("Line {}: invalid date \"{}\": {}", line_number, cell(date_column), error))?;
+            let figure = self
+                .parse_figure(cell(figure_column))
+                .map_err(|error| format!("Line {}: {}", line_number, error))?;
+            lines.push((date, figure));
+        }
+
+        if lines.first().zip(lines.last()).is_some_and(|((first, _), (last, _))| first > last) {
+            lines.reverse();
+        }
+
+        Ok(lines
+            .into_iter()
+            .map(|(date, figure)| if is_balance {
+                StatementLine::Balance(date, figure)
+            } else {
+                StatementLine::Amount(date, figure)
+            })
+            .collect())
+    }
+
+    /// Thousands separators and spaces are ignored
+    fn parse_figure(&self, raw_figure: &str) -> Result<Figure, String> {
+        let figure: String = raw_figure
+            .chars()
+            .filter(|character| !character.is_whitespace())
+            .filter(|character| *character == self.decimal_separator || !matches!(character, '.' | ',' | '\''))
+            .map(|character| if character == self.decimal_separator { '.' } else { character })
+            .collect();
+        Decimal::from_str(&figure).map_err(|error| format!("invalid amount \"{}\": {}", raw_figure, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn mkdate(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).expect("Can create date")
+    }
+
+    fn profile(value: serde_json::Value) -> CsvProfile {
+        serde_json::from_value(value).expect("Can parse profile")
+    }
+
+    fn as_tuples(lines: Vec<StatementLine>) -> Vec<(bool, NaiveDate, Figure)> {
+        lines
+            .into_iter()
+            .map(|line| match line {
+                StatementLine::Balance(date, figure) => (true, date, figure),
+                StatementLine::Amount(date, figure) => (false, date, figure),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn balances_most_recent_first() {
+        let profile = profile(json!({
+            "date_column": "Date",
+            "date_format": "%d/%m/%Y",
+            "decimal_separator": ",",
+            "delimiter": ";",
+            "balance_column": "Solde"
+        }));
+        let content = "Date;Libellé;Montant;Solde\n\
+            07/09/2025;Boulangerie;-3,20;1 204,30\n\
+            05/09/2025;Loyer;-800,00;\n\
+            02/09/2025;Salaire;2.000,00;2 007,50\n";
+
+        assert_eq!(
+            profile.read_statement(content).map(as_tuples),
+            Ok(vec![(true, mkdate(2), dec!(2007.50)), (true, mkdate(7), dec!(1204.30))])
+        );
+    }
+
+    #[test]
+    fn amounts() {
+        let profile = profile(json!({"date_column": "date", "amount_column": "amount"}));
+        let content = "date,amount,memo\n2025-09-02,\"-1,234.50\",\"Rent, September\"\n2025-09-03,12,Refund\n";
+
+        assert_eq!(
+            profile.read_statement(content).map(as_tuples),
+            Ok(vec![(false, mkdate(2), dec!(-1234.50)), (false, mkdate(3), dec!(12))])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let content = "date,amount\n2025-09-02,12\n2025-09-31,3\n";
+        assert_eq!(
+            profile(json!({"date_column": "date", "balance_column": "balance"})).read_statement(content).map(as_tuples),
+            Err("The file has no column named \"balance\"".to_string())
+        );
+        assert_eq!(
+            profile(json!({"date_column": "date", "amount_column": "amount"})).read_statement(content).map(as_tuples),
+            Err("Line 3: invalid date \"2025-09-31\": input is out of range".to_string())
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use super::DailyBalance;
+use crate::accounts::{AccountJson, Figure, QueriableAccount};
+use crate::error::ChoError;
+use crate::ignored_transaction::IgnoredTransaction;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A balance given by the bank, as of a date
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct OfxBalance {
+    pub figure: Figure,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct OfxTransaction {
+    pub date: NaiveDate,
+    pub amount: Figure,
+    pub name: String,
+}
+
+/// The statement of a single account, from an OFX 1.x (SGML) or 2.x (XML) file.
+/// Balances and amounts are signed from the point of view of the account holder.
+#[derive(Debug, Eq, PartialEq, Default)]
+pub struct OfxStatement {
+    pub currency: Option<String>,
+    pub ledger_balance: Option<OfxBalance>,
+    pub available_balance: Option<OfxBalance>,
+    pub transactions: Vec<OfxTransaction>,
+}
+
+enum Tag<'a> {
+    /// An aggregate when there is no value, an element otherwise
+    Open(&'a str, String),
+    Close(&'a str),
+}
+
+/// The tags of the file after the headers. SGML files do not close their elements, only their aggregates.
+fn tags(content: &str) -> Result<Vec<Tag<'_>>, String> {
+    let start = content.find("<OFX>").ok_or("The file is not an OFX statement")?;
+    let mut tags = vec![];
+    let mut rest = &content[start..];
+    while let Some(tag_start) = rest.find('<') {
+        let tag_end = rest[tag_start..]
+            .find('>')
+            .map(|end| tag_start + end)
+            .ok_or("The file ends in the middle of a tag")?;
+        let tag = &rest[tag_start + 1..tag_end];
+        rest = &rest[tag_end + 1..];
+        let value = &rest[..rest.find('<').unwrap_or(rest.len())];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            tags.push(Tag::Close(name.trim()));
+        } else if !tag.starts_with('?') && !tag.starts_with('!') {
+            tags.push(Tag::Open(tag.trim(), decode_entities(value.trim())));
+        }
+    }
+    Ok(tags)
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Dates are written YYYYMMDD, optionally followed by the time and the time zone
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or(format!("Invalid date \"{}\"", value))
+}
+
+fn parse_figure(value: &str) -> Result<Figure, String> {
+    Decimal::from_str(&value.replace(',', ".")).map_err(|error| format!("Invalid amount \"{}\": {}", value, error))
+}
+
+/// The value of an element of an aggregate
+fn element<'a>(aggregate: &str, elements: &'a HashMap<&str, String>, name: &str) -> Result<&'a String, String> {
+    elements.get(name).ok_or(format!("{} has no {}", aggregate, name))
+}
+
+fn balance(aggregate: &str, elements: &HashMap<&str, String>) -> Result<OfxBalance, String> {
+    Ok(OfxBalance {
+        figure: parse_figure(element(aggregate, elements, "BALAMT")?)?,
+        date: parse_date(element(aggregate, elements, "DTASOF")?)?,
+    })
+}
+
+pub fn parse(content: &str) -> Result<OfxStatement, String> {
+    let mut statement = OfxStatement::default();
+    let mut statements = 0;
+    let mut aggregates: Vec<(&str, HashMap<&str, String>)> = vec![];
+
+    for tag in tags(content)? {
+        match tag {
+            Tag::Open(name, value) if value.is_empty() => aggregates.push((name, HashMap::new())),
+            Tag::Open(name, value) => {
+                if let Some((_, elements)) = aggregates.last_mut() {
+                    elements.insert(name, value);
+                }
+            }
+            Tag::Close(name) => {
+                // In XML files, elements are closed as well
+                if !aggregates.iter().any(|(aggregate, _)| *aggregate == name) {
+                    continue;
+                }
+                while let Some((aggregate, elements)) = aggregates.pop() {
+                    match aggregate {
+                        "STMTTRN" => statement.transactions.push(OfxTransaction {
+                            date: parse_date(element(aggregate, &elements, "DTPOSTED")?)?,
+                            amount: parse_figure(element(aggregate, &elements, "TRNAMT")?)?,
+                            name: elements
+                                .get("NAME")
+                                .or(elements.get("MEMO"))
+                                .cloned()
+                                .unwrap_or_default(),
+                        }),
+                        "LEDGERBAL" => statement.ledger_balance = Some(balance(aggregate, &elements)?),
+                        "AVAILBAL" => statement.available_balance = Some(balance(aggregate, &elements)?),
+                        "STMTRS" | "CCSTMTRS" => {
+                            statements += 1;
+                            statement.currency = elements.get("CURDEF").cloned();
+                        }
+                        _ => {}
+                    }
+                    if aggregate == name {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    match statements {
+        0 => Err("The file contains no statement".to_string()),
+        1 => Ok(statement),
+        _ => Err(format!("The file contains {} statements, export one account at a time", statements)),
+    }
+}
+
+impl OfxStatement {
+    /// The balance at the end of each day of the statement, worked back from the ledger balance, or from the
+    /// available balance when there is none. The figures are recorded the way the account records them.
+    pub fn daily_balances(&self, account: &AccountJson) -> Result<Vec<DailyBalance>, ChoError> {
+        if let Some(currency) = self.currency.as_ref().filter(|currency| *currency != account.currency()) {
+            return Err(format!(
+                "The statement is in {} but {} is in {}",
+                currency,
+                account.name(),
+                account.currency()
+            ).into());
+        }
+        let balance = self
+            .ledger_balance
+            .as_ref()
+            .or(self.available_balance.as_ref())
+            .ok_or("The statement has no balance")?;
+
+        let mut balances = vec![DailyBalance { date: balance.date, figure: balance.figure }];
+        let mut transactions: Vec<&OfxTransaction> =
+            self.transactions.iter().filter(|transaction| transaction.date <= balance.date).collect();
+        transactions.sort_by_key(|transaction| transaction.date);
+        // The balance of a day is the one of the next day, without the transactions of the next day
+        for transaction in transactions.into_iter().rev() {
+            let Some(last_balance) = balances.last() else {
+                break;
+            };
+            if transaction.date < last_balance.date {
+                balances.push(DailyBalance { date: transaction.date, figure: last_balance.figure });
+            }
+        }
+        for index in 1..balances.len() {
+            let later_day = balances[index - 1].date;
+            let later_transactions: Figure = self
+                .transactions
+                .iter()
+                .filter(|transaction| transaction.date > balances[index].date && transaction.date <= later_day)
+                .map(|transaction| transaction.amount)
+                .sum();
+            balances[index].figure = balances[index - 1].figure - later_transactions;
+        }
+
+        balances.reverse();
+        Ok(balances
+            .into_iter()
+            .map(|balance| DailyBalance { date: balance.date, figure: account.signed_figure(balance.figure) })
+            .collect())
+    }
+
+    /// Each transaction as it would be written in the "ignored_transactions" list of the vault
+    pub fn ignored_transaction_candidates(&self, account: &AccountJson) -> Vec<IgnoredTransaction> {
+        let currency = self.currency.clone().unwrap_or(account.currency().clone());
+        self.transactions
+            .iter()
+            .map(|transaction| {
+                IgnoredTransaction::new(transaction.name.clone(), currency.clone(), transaction.amount, transaction.date)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn mkdate(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).expect("Can create date")
+    }
+
+    fn account(kind: &str) -> AccountJson {
+        serde_json::from_value(json!({
+            "name": "Account",
+            "currency": "EUR",
+            "kind": kind,
+            "amounts": [{"date": "2025-09-01", "amount": 500}]
+        })).expect("Can parse account")
+    }
+
+    #[test]
+    fn sgml_statement() {
+        let statement = parse(include_str!("fixtures/statement_v1.ofx")).expect("Can parse statement");
+
+        assert_eq!(statement, OfxStatement {
+            currency: Some("EUR".to_string()),
+            ledger_balance: Some(OfxBalance { figure: dec!(1687.60), date: mkdate(10) }),
+            available_balance: Some(OfxBalance { figure: dec!(1587.60), date: mkdate(10) }),
+            transactions: vec![
+                OfxTransaction { date: mkdate(2), amount: dec!(2000.00), name: "SALAIRE SEPTEMBRE".to_string() },
+                OfxTransaction { date: mkdate(5), amount: dec!(-800.00), name: "LOYER".to_string() },
+                OfxTransaction { date: mkdate(5), amount: dec!(-12.40), name: "BOULANGERIE & CAFE".to_string() },
+            ],
+        });
+        assert_eq!(statement.daily_balances(&account("asset")), Ok(vec![
+            DailyBalance { date: mkdate(2), figure: dec!(2500.00) },
+            DailyBalance { date: mkdate(5), figure: dec!(1687.60) },
+            DailyBalance { date: mkdate(10), figure: dec!(1687.60) },
+        ]));
+        assert_eq!(statement.ignored_transaction_candidates(&account("asset"))[1], IgnoredTransaction::new(
+            "LOYER".to_string(),
+            "EUR".to_string(),
+            dec!(-800.00),
+            mkdate(5),
+        ));
+    }
+
+    #[test]
+    fn xml_credit_card_statement() {
+        let statement = parse(include_str!("fixtures/statement_v2.ofx")).expect("Can parse statement");
+
+        assert_eq!(statement, OfxStatement {
+            currency: Some("USD".to_string()),
+            ledger_balance: None,
+            available_balance: Some(OfxBalance { figure: dec!(-245.99), date: mkdate(30) }),
+            transactions: vec![
+                OfxTransaction { date: mkdate(12), amount: dec!(-45.99), name: "BOOKSHOP".to_string() },
+            ],
+        });
+        assert_eq!(
+            statement.daily_balances(&account("credit_card")),
+            Err("The statement is in USD but Account is in EUR".into())
+        );
+
+        // Credit cards record what is owed
+        let statement = OfxStatement { currency: None, ..statement };
+        assert_eq!(statement.daily_balances(&account("credit_card")), Ok(vec![
+            DailyBalance { date: mkdate(12), figure: dec!(245.99) },
+            DailyBalance { date: mkdate(30), figure: dec!(245.99) },
+        ]));
+    }
+
+    #[test]
+    fn not_a_statement() {
+        assert_eq!(parse("date,amount\n"), Err("The file is not an OFX statement".to_string()));
+        assert_eq!(
+            parse("<OFX><STMTRS><STMTTRN><TRNAMT>12</STMTTRN></STMTRS></OFX>"),
+            Err("STMTTRN has no DTPOSTED".to_string())
+        );
+    }
+}
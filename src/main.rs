@@ -1,14 +1,20 @@
 mod accounts;
 mod cli;
 mod ignored_transaction;
+mod recurring_expense;
 mod period;
 mod vault;
 mod remaining_operation;
 mod predicted_income;
 mod buckets;
+mod check;
+mod chrono_stack;
+mod error;
+mod goal_feasibility;
+mod forecast;
+mod import;
 pub mod amounts;
 
-use crate::cli::remaining_operation;
 fn main() {
-    remaining_operation()
+    cli::run()
 }
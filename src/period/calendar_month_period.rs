@@ -1,3 +1,4 @@
+use crate::error::ChoError;
 use crate::period::{Period, PeriodsConfiguration};
 use chrono::{Datelike, Months, NaiveDate};
 use serde::Deserialize;
@@ -8,7 +9,7 @@ use crate::period::interface::ErrorPeriodsBetween::EndBeforeStart;
 pub struct CalendarMonthPeriodConfiguration {}
 
 impl PeriodsConfiguration for CalendarMonthPeriodConfiguration {
-    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, String> {
+    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, ChoError> {
         Ok(Period {
             start_date: date
                 .with_day(1)
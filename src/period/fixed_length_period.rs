@@ -1,3 +1,4 @@
+use crate::error::ChoError;
 use crate::period::interface::{ErrorPeriodsBetween, Period, PeriodsConfiguration};
 use chrono::{Days, NaiveDate};
 use serde::Deserialize;
@@ -27,11 +28,9 @@ impl FixedLengthPeriodConfiguration {
 }
 
 impl PeriodsConfiguration for FixedLengthPeriodConfiguration {
-    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, String> {
-        let period_number_for_date = self.period_number_for_date(date).or_else(
-            |_errortypecheck: ErrorStartBeforePeriodConfiguration| {
-                return Err("Date is before PeriodsConfiguration's start".to_string());
-            },
+    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, ChoError> {
+        let period_number_for_date = self.period_number_for_date(date).map_err(
+            |_errortypecheck: ErrorStartBeforePeriodConfiguration| ChoError::DateBeforePeriods(*date),
         )?;
 
         let start_from_config_start = period_number_for_date as u64 * self.period_in_days as u64;
@@ -81,7 +80,7 @@ impl PeriodsConfiguration for FixedLengthPeriodConfiguration {
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
-    use super::{FixedLengthPeriodConfiguration, PeriodsConfiguration};
+    use super::{ChoError, FixedLengthPeriodConfiguration, PeriodsConfiguration};
     use crate::period::Period;
     use chrono::NaiveDate;
     use crate::period::interface::ErrorPeriodsBetween;
@@ -200,10 +199,10 @@ mod tests {
 
     #[test]
     fn period_for_date__first_period__before_first_date() {
-        assert_eq!(
-            config().period_for_date(&date(10)).unwrap_err(),
-            "Date is before PeriodsConfiguration's start"
-        )
+        assert!(matches!(
+            config().period_for_date(&date(10)),
+            Err(ChoError::DateBeforePeriods(before_start)) if before_start == date(10)
+        ))
     }
     fn first_period() -> Period {
         return Period {
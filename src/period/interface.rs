@@ -1,11 +1,12 @@
 use crate::period::calendar_month_period::CalendarMonthPeriodConfiguration;
 use crate::period::fixed_length_period::FixedLengthPeriodConfiguration;
+use crate::error::ChoError;
 use crate::vault::VaultReadable;
 use chrono::NaiveDate;
 use clap::builder::Str;
 #[cfg(test)]
 use mockall::automock;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
 #[serde(tag = "type")]
@@ -30,7 +31,7 @@ impl PeriodConfigurationVaultValue {
 }
 
 impl PeriodsConfiguration for PeriodConfigurationVaultValue {
-    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, String> {
+    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, ChoError> {
         self.unpack().period_for_date(date)
     }
 
@@ -56,10 +57,10 @@ impl From<ErrorPeriodsBetween> for String {
 
 #[cfg_attr(test, automock)]
 pub trait PeriodsConfiguration {
-    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, String>;
+    fn period_for_date(&self, date: &NaiveDate) -> Result<Period, ChoError>;
     fn periods_between(&self, start: &NaiveDate, end: &NaiveDate) -> Result<u16, ErrorPeriodsBetween>;
 }
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub struct Period {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
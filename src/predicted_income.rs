@@ -1,39 +1,251 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use derive_builder::Builder;
 use rust_decimal::Decimal;
 use serde::Deserialize;
-use crate::period::{Period, PeriodConfigurationVaultValue};
+use crate::period::{Period, PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::amounts::{Add, Amount};
 use crate::amounts::exchange_rates::ExchangeRates;
-use crate::remaining_operation::core_types::{GroupBuilder, IllustrationValue, Operand, OperandBuilder};
+use crate::error::ChoError;
+use crate::recurring_expense::check_day_of_year;
+use crate::remaining_operation::core_types::{GroupBuilder, Operand, OperandBuilder};
 use crate::remaining_operation::core_types::group::Group;
 use crate::vault::{Vault, VaultReadable};
 
 
+/// The income expected each period. `figure` is the salary until the first of its `changes`,
+/// one-off and yearly payments come on top of it in the periods they fall in.
 #[cfg_attr(test, derive(Builder))]
 #[derive(Deserialize)]
 pub struct PredictedIncome{
     currency: String,
     figure: Decimal,
+    #[serde(default)]
+    #[cfg_attr(test, builder(default))]
+    changes: Vec<SalaryChange>,
+    #[serde(default)]
+    #[cfg_attr(test, builder(default))]
+    one_offs: Vec<OneOffIncome>,
+    #[serde(default)]
+    #[cfg_attr(test, builder(default))]
+    yearly: Vec<YearlyIncome>,
+}
+
+/// The salary becomes `figure` from `from` onward
+#[derive(Deserialize, Clone)]
+pub struct SalaryChange {
+    from: NaiveDate,
+    figure: Decimal,
+}
+
+/// An income received once, such as a bonus
+#[derive(Deserialize, Clone)]
+pub struct OneOffIncome {
+    date: NaiveDate,
+    figure: Decimal,
+}
+
+/// An income received every year on the same day. An income due on February 29 is only received in leap years.
+#[derive(Deserialize, Clone)]
+#[serde(try_from = "YearlyIncomeJson")]
+pub struct YearlyIncome {
+    month: u32,
+    day: u32,
+    figure: Decimal,
+}
+
+#[derive(Deserialize)]
+struct YearlyIncomeJson {
+    month: u32,
+    day: u32,
+    figure: Decimal,
+}
+
+impl TryFrom<YearlyIncomeJson> for YearlyIncome {
+    type Error = String;
+
+    fn try_from(yearly_json: YearlyIncomeJson) -> Result<Self, Self::Error> {
+        check_day_of_year(yearly_json.month, yearly_json.day)?;
+        Ok(YearlyIncome { month: yearly_json.month, day: yearly_json.day, figure: yearly_json.figure })
+    }
 }
 
 impl VaultReadable for PredictedIncome {
     const KEY: &'static str = "predicted_income";
 }
 
+/// How the income of a period is made up
+struct IncomeBreakdown {
+    salary: Amount,
+    /// None when the salary is the initial one
+    salary_effective_from: Option<NaiveDate>,
+    one_offs: Option<Amount>,
+    yearly: Option<Amount>,
+}
+
+impl IncomeBreakdown {
+    fn total(&self) -> Amount {
+        [&self.one_offs, &self.yearly]
+            .into_iter()
+            .flatten()
+            .fold(self.salary.clone(), |total, amount| total.add(amount))
+    }
+}
+
+impl PredictedIncome {
+    /// The income expected during the period
+    pub fn for_period(&self, period: &Period, exchange_rates: &ExchangeRates) -> Result<Amount, ChoError> {
+        Ok(self.breakdown(period, exchange_rates)?.total())
+    }
+
+    fn breakdown(&self, period: &Period, exchange_rates: &ExchangeRates) -> Result<IncomeBreakdown, ChoError> {
+        // The salary in effect at the start of the period is the one paid for the whole period
+        let change = self.changes
+            .iter()
+            .filter(|change| change.from <= period.start_date)
+            .max_by_key(|change| change.from);
+        let salary = exchange_rates.new_amount_at(
+            &self.currency,
+            change.map_or(self.figure, |change| change.figure),
+            &period.start_date,
+        )?;
+
+        let one_off_payments = self.one_offs
+            .iter()
+            .filter(|one_off| period.contains(&one_off.date))
+            .map(|one_off| (one_off.date, one_off.figure));
+
+        let yearly_payments = self.yearly
+            .iter()
+            .flat_map(|yearly| (period.start_date.year()..=period.end_date.year())
+                .filter_map(|year| NaiveDate::from_ymd_opt(year, yearly.month, yearly.day))
+                .filter(|date| period.contains(date))
+                .map(|date| (date, yearly.figure)));
+
+        Ok(IncomeBreakdown {
+            salary,
+            salary_effective_from: change.map(|change| change.from),
+            one_offs: self.sum(one_off_payments, exchange_rates)?,
+            yearly: self.sum(yearly_payments, exchange_rates)?,
+        })
+    }
+
+    /// None when there is no payment
+    fn sum(
+        &self,
+        mut payments: impl Iterator<Item = (NaiveDate, Decimal)>,
+        exchange_rates: &ExchangeRates,
+    ) -> Result<Option<Amount>, ChoError> {
+        payments.try_fold(None, |sum: Option<Amount>, (date, figure)| {
+            let amount = exchange_rates.new_amount_at(&self.currency, figure, &date)?;
+            Ok(Some(match sum {
+                Some(sum) => sum.add(&amount),
+                None => amount,
+            }))
+        })
+    }
+}
+
 impl OperandBuilder for PredictedIncome {
-    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, String> {
-        let amount = exchange_rates.new_amount(&self.currency, self.figure)?;
+    fn build(self, period_config: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
+        let breakdown = self.breakdown(&period_config.period_for_date(today)?, exchange_rates)?;
 
         Ok(Some(Operand{
             name: "Predicted Income".to_string(),
-            amount,
-            illustration: Vec::new(),
+            amount: breakdown.total(),
+            illustration: vec![
+                ("Salary".to_string(), breakdown.salary.into()),
+                ("Salary effective from".to_string(), breakdown.salary_effective_from.into()),
+                ("One-off income".to_string(), breakdown.one_offs.into()),
+                ("Yearly income".to_string(), breakdown.yearly.into()),
+            ],
         }))
     }
 }
 
 impl GroupBuilder<PredictedIncome> for PredictedIncome {
-    fn build(self) -> Result<(String, Vec<PredictedIncome>), String> {
+    fn build(self) -> Result<(String, Vec<PredictedIncome>), ChoError> {
         Ok(("Predicted Income".into(), vec![self]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use crate::remaining_operation::core_types::IllustrationValue;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn mkdate(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("Can create date")
+    }
+
+    fn build(today: NaiveDate) -> Operand {
+        let predicted_income: PredictedIncome = serde_json::from_value(json!({
+            "currency": "JPY",
+            "figure": "300000",
+            "changes": [
+                {"from": "2025-10-01", "figure": "320000"},
+                {"from": "2025-09-15", "figure": "310000"}
+            ],
+            "one_offs": [{"date": "2025-12-10", "figure": "500000"}],
+            "yearly": [{"month": 12, "day": 25, "figure": "10000"}, {"month": 2, "day": 29, "figure": "1000"}]
+        })).expect("Can parse predicted income");
+
+        OperandBuilder::build(
+            predicted_income,
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &today,
+            &ExchangeRates::for_tests(),
+        )
+            .expect("Can build predicted income")
+            .expect("Predicted income is never empty")
+    }
+
+    #[test]
+    fn initial_salary() {
+        let ex = ExchangeRates::for_tests();
+        assert_eq!(build(mkdate(2025, 9, 20)), Operand {
+            name: "Predicted Income".to_string(),
+            amount: ex.yen("300000"),
+            illustration: vec![
+                ("Salary".to_string(), IllustrationValue::Amount(ex.yen("300000"))),
+                ("Salary effective from".to_string(), IllustrationValue::NullDate),
+                ("One-off income".to_string(), IllustrationValue::NullAmount),
+                ("Yearly income".to_string(), IllustrationValue::NullAmount),
+            ],
+        });
+    }
+
+    #[test]
+    fn salary_change_one_off_and_yearly_income() {
+        let ex = ExchangeRates::for_tests();
+        assert_eq!(build(mkdate(2025, 12, 1)), Operand {
+            name: "Predicted Income".to_string(),
+            amount: ex.yen("830000"),
+            illustration: vec![
+                ("Salary".to_string(), IllustrationValue::Amount(ex.yen("320000"))),
+                ("Salary effective from".to_string(), IllustrationValue::Date(mkdate(2025, 10, 1))),
+                ("One-off income".to_string(), IllustrationValue::Amount(ex.yen("500000"))),
+                ("Yearly income".to_string(), IllustrationValue::Amount(ex.yen("10000"))),
+            ],
+        });
+    }
+
+    #[test]
+    fn yearly_income_on_a_leap_day() {
+        let ex = ExchangeRates::for_tests();
+        assert_eq!(build(mkdate(2027, 2, 1)).amount, ex.yen("320000"));
+        assert_eq!(build(mkdate(2028, 2, 1)).amount, ex.yen("321000"));
+    }
+
+    #[test]
+    fn yearly_income_on_an_impossible_day() {
+        let predicted_income = serde_json::from_value::<PredictedIncome>(json!({
+            "currency": "JPY",
+            "figure": "300000",
+            "yearly": [{"month": 2, "day": 30, "figure": "10000"}]
+        }));
+        assert_eq!(predicted_income.err().map(|error| error.to_string()), Some("Month 2 and day 30 are not a date".to_string()));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::amounts::exchange_rates::ExchangeRates;
+use crate::amounts::Amount;
+use crate::error::ChoError;
+use crate::period::{Period, PeriodConfigurationVaultValue, PeriodsConfiguration};
+use crate::remaining_operation::core_types::{GroupBuilder, IllustrationValue, Operand, OperandBuilder};
+use crate::vault::VaultReadable;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// An expense paid on a regular schedule, such as the rent or a subscription. `amount` is what is paid each time.
+#[derive(Deserialize, Clone)]
+pub struct RecurringExpense {
+    name: String,
+    currency: String,
+    amount: Decimal,
+    frequency: Frequency,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "FrequencyJson")]
+enum Frequency {
+    /// Paid on that day of every month, from 1 to 31. In the months that are too short, eg. on the 31st in
+    /// February, it is paid on their last day.
+    Monthly { day: u32 },
+    /// Paid on that date every year. An expense due on February 29 is only paid in leap years.
+    Yearly { month: u32, day: u32 },
+    /// Paid on `from`, then every `days` days
+    Every { days: u32, from: NaiveDate },
+}
+
+#[derive(Deserialize)]
+enum FrequencyJson {
+    #[serde(rename = "monthly")]
+    Monthly { day: u32 },
+    #[serde(rename = "yearly")]
+    Yearly { month: u32, day: u32 },
+    #[serde(rename = "every")]
+    Every { days: u32, from: NaiveDate },
+}
+
+impl TryFrom<FrequencyJson> for Frequency {
+    type Error = String;
+
+    fn try_from(frequency_json: FrequencyJson) -> Result<Self, Self::Error> {
+        match frequency_json {
+            FrequencyJson::Monthly { day } if !(1..=31).contains(&day) => {
+                Err(format!("A monthly expense cannot be paid on day {}, it must be between 1 and 31", day))
+            }
+            FrequencyJson::Monthly { day } => Ok(Frequency::Monthly { day }),
+            FrequencyJson::Yearly { month, day } => {
+                check_day_of_year(month, day)?;
+                Ok(Frequency::Yearly { month, day })
+            }
+            FrequencyJson::Every { days: 0, .. } => Err("An expense cannot be paid every 0 days".to_string()),
+            FrequencyJson::Every { days, from } => Ok(Frequency::Every { days, from }),
+        }
+    }
+}
+
+/// The month and day must be a date of at least some years. February 29 is one, it exists in leap years.
+pub fn check_day_of_year(month: u32, day: u32) -> Result<(), String> {
+    const LEAP_YEAR: i32 = 2000;
+    match NaiveDate::from_ymd_opt(LEAP_YEAR, month, day) {
+        Some(_) => Ok(()),
+        None => Err(format!("Month {} and day {} are not a date", month, day)),
+    }
+}
+
+impl Frequency {
+    /// The dates the expense is paid on during the period, in chronological order
+    fn occurrences(&self, period: &Period) -> Result<Vec<NaiveDate>, ChoError> {
+        let dates = match self {
+            Frequency::Monthly { day } => {
+                let mut dates = vec![];
+                let mut month_start = period.start_date.with_day(1);
+                while let Some(start) = month_start.filter(|start| *start <= period.end_date) {
+                    let next_month_start = start
+                        .checked_add_months(Months::new(1))
+                        .ok_or("Could not compute the start of the next month")?;
+                    let month_end = next_month_start.pred_opt().ok_or("Could not compute the end of the month")?;
+                    dates.push(start.with_day(*day).unwrap_or(month_end));
+                    month_start = Some(next_month_start);
+                }
+                dates
+            }
+            Frequency::Yearly { month, day } => (period.start_date.year()..=period.end_date.year())
+                .filter_map(|year| NaiveDate::from_ymd_opt(year, *month, *day))
+                .collect(),
+            Frequency::Every { days, from } => {
+                let step = i64::from(*days);
+                let days_since_from = (period.start_date - *from).num_days().max(0);
+                // The first payment on or after the start of the period
+                let mut date = *from + Duration::days((days_since_from + step - 1) / step * step);
+                let mut dates = vec![];
+                while date <= period.end_date {
+                    dates.push(date);
+                    date += Duration::days(step);
+                }
+                dates
+            }
+        };
+
+        Ok(dates.into_iter().filter(|date| period.contains(date)).collect())
+    }
+}
+
+impl RecurringExpense {
+    /// What is paid during the period
+    pub fn for_period(&self, period: &Period, exchange_rates: &ExchangeRates) -> Result<Amount, ChoError> {
+        let occurrences = self.frequency.occurrences(period)?;
+        exchange_rates.new_amount(&self.currency, self.amount * Decimal::from(occurrences.len()))
+    }
+}
+
+impl OperandBuilder for RecurringExpense {
+    fn build(self, period_configuration: &PeriodConfigurationVaultValue, today: &NaiveDate, exchange_rates: &ExchangeRates) -> Result<Option<Operand>, ChoError> {
+        let current_period = period_configuration.period_for_date(today)?;
+        let occurrences = self.frequency.occurrences(&current_period)?;
+        let Some(last_occurrence) = occurrences.last() else {
+            return Ok(None);
+        };
+
+        // The accounts already show the payments made until today, only the ones to come remain to be paid
+        let upcoming: Vec<&NaiveDate> = occurrences.iter().filter(|date| *date > today).collect();
+        let amount = exchange_rates.new_amount(&self.currency, -self.amount * Decimal::from(upcoming.len()))?;
+
+        Ok(Some(Operand {
+            name: self.name.clone(),
+            amount,
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(upcoming.is_empty())),
+                ("Date".to_string(), IllustrationValue::Date(**upcoming.first().unwrap_or(&last_occurrence))),
+            ],
+        }))
+    }
+}
+
+pub type RecurringExpensesVaultValue = Vec<RecurringExpense>;
+impl VaultReadable for RecurringExpensesVaultValue {
+    const KEY: &'static str = "recurring_expenses";
+}
+
+impl GroupBuilder<RecurringExpense> for RecurringExpensesVaultValue {
+    fn build(self) -> Result<(String, Vec<RecurringExpense>), ChoError> {
+        Ok(("Recurring Expenses".into(), self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::period::CalendarMonthPeriodConfiguration;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn mkdate(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("Can create date")
+    }
+
+    fn occurrences(frequency: serde_json::Value, start_date: NaiveDate, end_date: NaiveDate) -> Vec<NaiveDate> {
+        serde_json::from_value::<Frequency>(frequency)
+            .expect("Can parse frequency")
+            .occurrences(&Period { start_date, end_date })
+            .expect("Can compute occurrences")
+    }
+
+    #[test]
+    fn frequencies() {
+        assert_eq!(
+            occurrences(json!({"monthly": {"day": 31}}), mkdate(2024, 1, 15), mkdate(2024, 3, 30)),
+            vec![mkdate(2024, 1, 31), mkdate(2024, 2, 29)]
+        );
+        assert_eq!(
+            occurrences(json!({"yearly": {"month": 2, "day": 29}}), mkdate(2025, 1, 1), mkdate(2028, 12, 31)),
+            vec![mkdate(2028, 2, 29)]
+        );
+        assert_eq!(
+            occurrences(json!({"every": {"days": 14, "from": "2025-08-28"}}), mkdate(2025, 9, 1), mkdate(2025, 9, 30)),
+            vec![mkdate(2025, 9, 11), mkdate(2025, 9, 25)]
+        );
+        assert_eq!(
+            occurrences(json!({"every": {"days": 7, "from": "2025-09-20"}}), mkdate(2025, 9, 1), mkdate(2025, 9, 30)),
+            vec![mkdate(2025, 9, 20), mkdate(2025, 9, 27)]
+        );
+    }
+
+    #[test]
+    fn invalid_frequencies() {
+        let error = |frequency: serde_json::Value| serde_json::from_value::<Frequency>(frequency).unwrap_err().to_string();
+
+        assert_eq!(error(json!({"monthly": {"day": 0}})), "A monthly expense cannot be paid on day 0, it must be between 1 and 31");
+        assert_eq!(error(json!({"monthly": {"day": 45}})), "A monthly expense cannot be paid on day 45, it must be between 1 and 31");
+        assert_eq!(error(json!({"yearly": {"month": 13, "day": 1}})), "Month 13 and day 1 are not a date");
+        assert_eq!(error(json!({"yearly": {"month": 4, "day": 31}})), "Month 4 and day 31 are not a date");
+        assert_eq!(error(json!({"every": {"days": 0, "from": "2025-09-01"}})), "An expense cannot be paid every 0 days");
+    }
+
+    #[test]
+    fn build() {
+        let ex = ExchangeRates::for_tests();
+        let expense: RecurringExpense = serde_json::from_value(json!({
+            "name": "Gym",
+            "currency": "JPY",
+            "amount": "3000",
+            "frequency": {"every": {"days": 14, "from": "2025-08-28"}}
+        })).expect("Can parse recurring expense");
+        let build = |today: NaiveDate| OperandBuilder::build(
+            expense.clone(),
+            &PeriodConfigurationVaultValue::CalendarMonth(CalendarMonthPeriodConfiguration {}),
+            &today,
+            &ex,
+        ).expect("Can build operand");
+
+        assert_eq!(build(mkdate(2025, 9, 11)), Some(Operand {
+            name: "Gym".to_string(),
+            amount: ex.yen("-3000"),
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(false)),
+                ("Date".to_string(), IllustrationValue::Date(mkdate(2025, 9, 25))),
+            ],
+        }));
+        assert_eq!(build(mkdate(2025, 9, 26)), Some(Operand {
+            name: "Gym".to_string(),
+            amount: ex.yen("0"),
+            illustration: vec![
+                ("Paid".to_string(), IllustrationValue::Bool(true)),
+                ("Date".to_string(), IllustrationValue::Date(mkdate(2025, 9, 25))),
+            ],
+        }));
+        assert_eq!(
+            expense.for_period(&Period { start_date: mkdate(2025, 10, 1), end_date: mkdate(2025, 10, 31) }, &ex),
+            Ok(ex.yen("6000"))
+        );
+    }
+}
@@ -1,13 +1,17 @@
 use crate::amounts::exchange_rates::ExchangeRates;
 use crate::amounts::{Add, Amount, CurrencyIdent};
+use crate::error::{ChoError, Context};
 use crate::period::{Period, PeriodConfigurationVaultValue, PeriodsConfiguration};
-use chrono::{Local, NaiveDate};
+use chrono::NaiveDate;
 use group::Group;
 use rust_decimal_macros::dec;
 use crate::accounts::AccountGetter;
+use crate::accounts::loan::LoanRepaymentsGetter;
+use crate::accounts::savings::SavingsInterestGetter;
 use crate::buckets::BucketsVaultValue;
 use crate::ignored_transaction::IgnoredTransactionsVaultValues;
 use crate::predicted_income::PredictedIncome;
+use crate::recurring_expense::RecurringExpensesVaultValue;
 use crate::vault::{Vault, VaultReadable};
 
 /* Entrypoint */
@@ -31,26 +35,39 @@ impl RemainingOperation {
             exchange_rates,
         }
     }
+    /// Build the screen from the values of the vault. The accounts and the periods are read by the caller, which
+    /// usually needs them too.
     pub fn from_vault_values<V: Vault>(
         include_predicted_income: bool,
         vault: &V,
+        periods_configuration: PeriodConfigurationVaultValue,
+        date: NaiveDate,
+        accounts: AccountGetter,
         exchange_rates: ExchangeRates,
-    ) -> Result<RemainingOperation, String> {
-        let mut operation = RemainingOperation::new(
-            PeriodConfigurationVaultValue::from_vault(vault)?,
-            Local::now().date_naive(),
-            exchange_rates,
-        );
-        operation.add_group(AccountGetter::from_vault(vault)?)?;
+    ) -> Result<RemainingOperation, ChoError> {
+        let mut operation = RemainingOperation::new(periods_configuration, date, exchange_rates);
+        let loan_repayments = LoanRepaymentsGetter::from_accounts(&accounts);
+        let savings_interest = SavingsInterestGetter::from_accounts(&accounts);
+        operation.add_group(accounts)?;
+        if !loan_repayments.is_empty() {
+            operation.add_group(loan_repayments)?;
+        }
         operation.add_group(BucketsVaultValue::from_vault(vault)?)?;
         operation.add_group(IgnoredTransactionsVaultValues::from_vault(vault)?)?;
+        if let Some(recurring_expenses) = RecurringExpensesVaultValue::from_vault_optional(vault)? {
+            operation.add_group(recurring_expenses)?;
+        }
         if include_predicted_income {
             operation.add_group(PredictedIncome::from_vault(vault)?)?;
+
+            if !savings_interest.is_empty() {
+                operation.add_group(savings_interest)?;
+            }
         }
         Ok(operation)
     }
 
-    pub fn add_group<O: OperandBuilder, B: GroupBuilder<O>>(&mut self, builder: B) -> Result<(), String> {
+    pub fn add_group<O: OperandBuilder, B: GroupBuilder<O>>(&mut self, builder: B) -> Result<(), ChoError> {
         let group = Group::from_group_builder(builder, &self.exchange_rates, &self.periods_configuration, &self.date)?;
         self.groups.push(group);
         Ok(())
@@ -60,19 +77,19 @@ impl RemainingOperation {
     pub fn execute(
         &self,
         target_currency: &CurrencyIdent,
-    ) -> Result<RemainingOperationScreen, String> {
+    ) -> Result<RemainingOperationScreen, ChoError> {
 
         let current_period = self
             .periods_configuration
             .period_for_date(&self.date)
-            .map_err(|error| "Failed to fetch Periods Configuration: ".to_string() + &error)?;
+            .context(|| "Failed to fetch Periods Configuration")?;
 
         let remaining_operation_screen_group = self.groups
             .clone()
             .into_iter()
             .map(|group| group.into_remaining_operation_screen_group(
                 &self.exchange_rates, target_currency))
-            .collect::<Result<Vec<RemainingOperationScreenGroup>, String>>()?;
+            .collect::<Result<Vec<RemainingOperationScreenGroup>, ChoError>>()?;
 
         let mut remaining: Amount = self.exchange_rates.new_amount(target_currency, dec!(0))?;
         for group in self.groups.iter() {
@@ -93,7 +110,7 @@ impl RemainingOperation {
 pub trait GroupBuilder<B: OperandBuilder> {
     fn build(
         self,
-    ) -> Result<(String, Vec<B>), String>;
+    ) -> Result<(String, Vec<B>), ChoError>;
 }
 
 pub trait OperandBuilder {
@@ -105,7 +122,7 @@ pub trait OperandBuilder {
         // Once the entire codebase adopts ExchangeRates, we won't need to pass it around
         // TODO Remove exchange rate from this interface
         exchange_rates: &ExchangeRates,
-    ) -> Result<Option<Operand>, String>;
+    ) -> Result<Option<Operand>, ChoError>;
 }
 
 /* Output types */
@@ -138,6 +155,7 @@ pub mod group {
     use crate::amounts::{Add, Amount, Currency, CurrencyIdent};
     use crate::amounts::exchange_rates::ExchangeRates;
     use super::{GroupBuilder, Operand, OperandBuilder, RemainingOperationScreenGroup};
+    use crate::error::ChoError;
 
     #[derive(Clone, PartialEq, Eq, Debug)]
     pub struct Group {
@@ -148,7 +166,7 @@ pub mod group {
 
 
     impl Group {
-        pub fn new(name: &str, operands: Vec<Operand>) -> Result<Group, String> {
+        pub fn new(name: &str, operands: Vec<Operand>) -> Result<Group, ChoError> {
             let mut group = Group {
                 name: name.to_string(),
                 operands: vec![],
@@ -167,13 +185,13 @@ pub mod group {
             exchange_rates: &ExchangeRates,
             period_configuration: &PeriodConfigurationVaultValue,
             today: &NaiveDate,
-        ) -> Result<Group, String>{
+        ) -> Result<Group, ChoError>{
             let (name, operand_builders) = group_builder.build()?;
 
             let operands: Vec<Operand> = operand_builders
                 .into_iter()
                 .map(|operand_builder| operand_builder.build(period_configuration, today, &exchange_rates))
-                .collect::<Result<Vec<Option<Operand>>, String>>()?
+                .collect::<Result<Vec<Option<Operand>>, ChoError>>()?
                 .into_iter()
                 .filter_map(|operand| operand)
                 .collect();
@@ -181,7 +199,7 @@ pub mod group {
             Group::new(&name, operands)
         }
 
-        pub fn add_operands(&mut self, o: Operand) -> Result<(), String> {
+        pub fn add_operands(&mut self, o: Operand) -> Result<(), ChoError> {
             if self.illustration_fields == None {
                 let fields = o.illustration.iter().map(|(k, _)| k.clone()).collect();
                 self.illustration_fields = Some(fields);
@@ -193,7 +211,7 @@ pub mod group {
                         o.name,
                         field_names,
                         illustration_fields
-                    ));
+                    ).into());
                 }
             }
             self.operands.push(o);
@@ -209,7 +227,7 @@ pub mod group {
             self,
             exchange_rates: &ExchangeRates,
             target_currency: &CurrencyIdent,
-        ) -> Result<RemainingOperationScreenGroup, String> {
+        ) -> Result<RemainingOperationScreenGroup, ChoError> {
             let total = self.operands
                 .iter()
                 .fold(exchange_rates.new_amount(target_currency, dec!(0))?, |acc, operand| {
@@ -247,6 +265,7 @@ pub enum IllustrationValue {
     NullAmount,
     Bool(bool),
     Date(NaiveDate),
+    NullDate,
 }
 
 impl From<Amount> for IllustrationValue {
@@ -263,6 +282,12 @@ impl From<Option<Amount>> for IllustrationValue {
     }
 }
 
+impl From<Option<NaiveDate>> for IllustrationValue {
+    fn from(value: Option<NaiveDate>) -> Self {
+        value.map_or(IllustrationValue::NullDate, IllustrationValue::Date)
+    }
+}
+
 pub type Illustration = Vec<(String, IllustrationValue)>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -274,6 +299,7 @@ pub struct Operand {
 
 #[cfg(test)]
 mod test {
+    use crate::error::ChoError;
     use crate::remaining_operation::core_types::{IllustrationValue, RemainingOperationScreen};
     use chrono::NaiveDate;
     use rust_decimal::Decimal;
@@ -296,7 +322,7 @@ mod test {
     }
 
     impl<OB: OperandBuilder> GroupBuilder<OB> for TestGroupBuilder<OB> {
-        fn build(self) -> Result<(String, Vec<OB>), String> {
+        fn build(self) -> Result<(String, Vec<OB>), ChoError> {
             Ok((self.name, self.operand_builders))
         }
     }
@@ -334,9 +360,9 @@ mod test {
             "account in euros left".to_string(),
             "EUR".to_string(),
             vec![
-                (mkdate(7, 1), 1000),
-                (mkdate(8, 2), 1500),
-                (mkdate(8, 3), 2200),
+                (mkdate(7, 1), dec!(1000)),
+                (mkdate(8, 2), dec!(1500)),
+                (mkdate(8, 3), dec!(2200)),
             ],
         );
 
@@ -344,9 +370,9 @@ mod test {
             "account in euros right".to_string(),
             "EUR".to_string(),
             vec![
-                (mkdate(7, 15), 500),
-                (mkdate(8, 2), 500),
-                (mkdate(8, 3), 300),
+                (mkdate(7, 15), dec!(500)),
+                (mkdate(8, 2), dec!(500)),
+                (mkdate(8, 3), dec!(300)),
             ],
         );
 
@@ -354,7 +380,7 @@ mod test {
             "account in yen left".to_string(),
             "JPY".to_string(),
             vec![
-                (mkdate(7, 31), 500),
+                (mkdate(7, 31), dec!(500)),
             ],
         );
 
@@ -362,8 +388,8 @@ mod test {
             "account in yen right".to_string(),
             "JPY".to_string(),
             vec![
-                (mkdate(7, 2), 700),
-                (mkdate(8, 15), 700),
+                (mkdate(7, 2), dec!(700)),
+                (mkdate(8, 15), dec!(700)),
             ],
         );
 
@@ -381,6 +407,7 @@ mod test {
 
         let bucket_must_commit: Bucket = from_value(json!({
             "name": "Goal must commit",
+            "currency": "JPY",
             "lines": [
                 "2023/07/01 TARG ¥200 2023/08/31",
                 "2023/07/18 DEPO ¥150"
@@ -389,6 +416,7 @@ mod test {
 
         let bucket_already_committed: Bucket = from_value(json!({
             "name": "Goal already committed",
+            "currency": "JPY",
             "lines": [
                 "2023/07/01 TARG ¥500 2023/08/31",
                 "2023/07/18 DEPO ¥100",
@@ -483,7 +511,7 @@ mod test {
                                 illustration: vec![
                                     ("Period start amount".into(), IllustrationValue::Amount(exchange_rates.euro("1000"))),
                                     ("Period end amount".into(), IllustrationValue::Amount(exchange_rates.euro("2200"))),
-                                    ("Committed".into(), IllustrationValue::Bool(true)),
+                                    ("Committed".into(), IllustrationValue::Bool(false)),
                                     ("Difference".into(), IllustrationValue::Amount(exchange_rates.euro("1200"))),
                                 ],
                             },
@@ -493,7 +521,7 @@ mod test {
                                 illustration: vec![
                                     ("Period start amount".into(), IllustrationValue::Amount(exchange_rates.euro("500"))),
                                     ("Period end amount".into(), IllustrationValue::Amount(exchange_rates.euro("300"))),
-                                    ("Committed".into(), IllustrationValue::Bool(true)),
+                                    ("Committed".into(), IllustrationValue::Bool(false)),
                                     ("Difference".into(), IllustrationValue::Amount(exchange_rates.euro("-200"))),
                                 ],
                             },
@@ -503,7 +531,7 @@ mod test {
                                 illustration: vec![
                                     ("Period start amount".into(), IllustrationValue::Amount(exchange_rates.yen("500"))),
                                     ("Period end amount".into(), IllustrationValue::Amount(exchange_rates.yen("500"))),
-                                    ("Committed".into(), IllustrationValue::Bool(true)),
+                                    ("Committed".into(), IllustrationValue::Bool(false)),
                                     ("Difference".into(), IllustrationValue::Amount(exchange_rates.yen("0"))),
                                 ],
                             },
@@ -513,7 +541,7 @@ mod test {
                                 illustration: vec![
                                     ("Period start amount".into(), IllustrationValue::Amount(exchange_rates.yen("700"))),
                                     ("Period end amount".into(), IllustrationValue::Amount(exchange_rates.yen("700"))),
-                                    ("Committed".into(), IllustrationValue::Bool(true)),
+                                    ("Committed".into(), IllustrationValue::Bool(false)),
                                     ("Difference".into(), IllustrationValue::Amount(exchange_rates.yen("0"))),
                                 ],
                             },
@@ -595,9 +623,19 @@ mod test {
                         operands: vec![Operand {
                             name: "Predicted Income".to_string(),
                             amount: exchange_rates.yen("400"),
-                            illustration: vec![],
+                            illustration: vec![
+                                ("Salary".into(), IllustrationValue::Amount(exchange_rates.yen("400"))),
+                                ("Salary effective from".into(), IllustrationValue::NullDate),
+                                ("One-off income".into(), IllustrationValue::NullAmount),
+                                ("Yearly income".into(), IllustrationValue::NullAmount),
+                            ],
                         }],
-                        illustration_fields: vec![],
+                        illustration_fields: vec![
+                            "Salary".into(),
+                            "Salary effective from".into(),
+                            "One-off income".into(),
+                            "Yearly income".into(),
+                        ],
                         total: exchange_rates.euro("200.00")
                     }
                 ],
@@ -2,6 +2,7 @@ use chrono::NaiveDate;
 use crate::period::{Period, PeriodConfigurationVaultValue};
 use crate::amounts::{Amount, Sub};
 use crate::amounts::exchange_rates::ExchangeRates;
+use crate::error::ChoError;
 use crate::remaining_operation::core_types::{Illustration, IllustrationValue, Operand, OperandBuilder};
 
 pub enum TimelineOperandEnd {
@@ -17,7 +18,7 @@ pub struct TimelineOperandBuilderHelper {
 impl TimelineOperandBuilderHelper {
     pub fn build(
         self,
-    ) -> Result<Option<Operand>, String> {
+    ) -> Result<Option<Operand>, ChoError> {
         let (end_amount, predicted) = match &self.wrapper_end_amount {
             TimelineOperandEnd::Current(amount) => (amount.clone(), false),
             TimelineOperandEnd::Predicted(amount) => (amount.clone(), true)
@@ -1,16 +1,26 @@
+use crate::error::{ChoError, Context};
 use serde::de::DeserializeOwned;
-use serde_json::{from_reader, from_value, Value};
-use std::fs::File;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{from_str, from_value, to_value, Map, Serializer, Value};
+use std::fs::{canonicalize, metadata, read_to_string, remove_file, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use tempfile::{tempdir, TempDir};
+use tempfile::{tempdir, NamedTempFile, TempDir};
 
 pub struct VaultImpl {
     pub(crate) path: PathBuf,
 }
 
 pub trait Vault {
-    fn read_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<T, String>;
+    fn read_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<T, ChoError>;
+
+    /// Same as `read_vault_values`, but a missing key is not an error
+    fn read_optional_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<Option<T>, ChoError>;
+
+    /// Replace the value of a key, or add it after the others. The other keys are left as they are, in the same order.
+    /// The whole file is written again, keeping its indentation (see `to_json_like`) but not its other whitespace.
+    fn write_vault_values<T: Serialize>(&self, name: String, value: &T) -> Result<(), ChoError>;
 
     // TODO - Path is this abstraction leaking because of the way we first wrote the accounts module
     //        (it does not rely on the "read_from_vault" impl). We will need to correct this if we
@@ -23,7 +33,7 @@ impl VaultImpl {
     pub(crate) fn create_mocked_vault(content: Value) -> (TempDir, VaultImpl) {
         let directory = tempdir().unwrap();
         let path = Path::join(directory.path(), "config.json");
-        let mut file = File::create(path).unwrap();
+        let mut file = std::fs::File::create(path).unwrap();
         file.write_all(&content.to_string().into_bytes()).unwrap();
 
         let path = directory.path().to_path_buf();
@@ -31,29 +41,126 @@ impl VaultImpl {
     }
 }
 
+/// Replace the content of a file without leaving it half written: the content goes to a temporary file
+/// next to it, which is then renamed over it. The temporary file takes the permissions of the file it replaces, and
+/// a symbolic link is left in place: the file it points to is the one replaced.
+pub fn write_atomically(path: &Path, content: &str) -> Result<(), ChoError> {
+    if !path.exists() {
+        return write_new_file(path, content);
+    }
+    let path = canonicalize(path).map_err(|source| ChoError::File { path: path.to_path_buf(), source })?;
+    let file_error = |source| ChoError::File { path: path.clone(), source };
+    let permissions = metadata(&path).map_err(file_error)?.permissions();
+    let directory = path.parent().ok_or(format!("Could not find the directory of {}", path.display()))?;
+    let mut file = NamedTempFile::new_in(directory)
+        .and_then(|mut file| file.write_all(content.as_bytes()).map(|_| file))
+        .and_then(|file| file.as_file().set_permissions(permissions).map(|_| file))
+        .map_err(file_error)
+        .context(|| "Could not write a temporary file next to it")?;
+    file.as_file_mut()
+        .sync_all()
+        .map_err(file_error)
+        .context(|| "Could not write a temporary file next to it")?;
+    file.persist(&path)
+        .map(|_| ())
+        .map_err(|error| file_error(error.error))
+        .context(|| "Could not replace the file")
+}
+
+/// A file that did not exist has nothing to lose if the write fails: it is created in place, with the default
+/// permissions, and removed if it cannot be written whole
+fn write_new_file(path: &Path, content: &str) -> Result<(), ChoError> {
+    let write = || {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    write().map_err(|source| {
+        let _ = remove_file(path);
+        ChoError::File { path: path.to_path_buf(), source }
+    })
+}
+
+/// Serialize a value the way the file it replaces was laid out: on one line if it was, otherwise with the same
+/// indentation. Without a previous content, or when it cannot be told, values are indented with two spaces.
+pub fn to_json_like<T: Serialize>(value: &T, previous_content: Option<&str>) -> Result<String, serde_json::Error> {
+    let previous_content = previous_content.map(str::trim).unwrap_or("\n");
+    if !previous_content.contains('\n') {
+        return serde_json::to_string(value).map(|content| content + "\n");
+    }
+    let indentation = previous_content
+        .lines()
+        .skip(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indentation| !indentation.is_empty())
+        .unwrap_or("  ");
+
+    let mut content = Vec::new();
+    let mut serializer = Serializer::with_formatter(&mut content, PrettyFormatter::with_indent(indentation.as_bytes()));
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(content).expect("serde_json writes UTF-8") + "\n")
+}
+
+impl VaultImpl {
+    fn configuration_path(&self) -> PathBuf {
+        self.path.join("config.json")
+    }
+
+    fn read_configuration_content(&self) -> Result<String, ChoError> {
+        let path = self.configuration_path();
+        read_to_string(&path).map_err(|source| ChoError::File { path, source })
+    }
+
+    fn parse_configuration(&self, content: &str) -> Result<Map<String, Value>, ChoError> {
+        from_str(content).context(|| self.configuration_path().display().to_string())
+    }
+
+    fn read_configuration(&self) -> Result<Map<String, Value>, ChoError> {
+        self.parse_configuration(&self.read_configuration_content()?)
+    }
+
+    fn read_optional_value(&self, name: &String) -> Result<Option<Value>, ChoError> {
+        self.read_configuration()
+            .map(|configuration| configuration.get(name).cloned())
+            .context(|| format!("Could not read key {}", name))
+    }
+
+    fn decode_value<T: DeserializeOwned>(&self, name: &String, value: Value) -> Result<T, ChoError> {
+        from_value(value).context(|| self.decoding_context(name))
+    }
+
+    fn encoding_context(&self, name: &String) -> String {
+        format!("Could not write key {} in configuration file {}", name, self.configuration_path().display())
+    }
+
+    fn decoding_context(&self, name: &String) -> String {
+        format!("Could not decode key {} in configuration file {}", name, self.configuration_path().display())
+    }
+}
+
 impl Vault for VaultImpl {
-    fn read_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<T, String> {
-        let path = self.path.join("config.json");
-        let vault_values = File::open(&path)
-            .map_err(|e| e.to_string())
-            .and_then(|file| from_reader(file).map_err(|e| e.to_string()))
-            .and_then(|json: Value| {
-                json.get(&name)
-                    .cloned()
-                    .ok_or(format!("Could not find key: {}", &name))
-            })
-            .and_then(|subconfig| -> Result<T, String> {
-                from_value(subconfig).map_err(|e| e.to_string())
-            });
-        vault_values.map_err(|e| {
-            format!(
-                "Could not decode key {} in configuration file {}: {}",
-                name,
-                path.to_str()
-                    .unwrap_or("(path cannot be turned into a string)"),
-                e
-            )
-        })
+    fn read_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<T, ChoError> {
+        let value = self.read_optional_value(&name)?
+            .ok_or_else(|| ChoError::MissingKey(name.clone()))
+            .context(|| self.decoding_context(&name))?;
+        self.decode_value(&name, value)
+    }
+
+    fn read_optional_vault_values<T: DeserializeOwned>(&self, name: String) -> Result<Option<T>, ChoError> {
+        self.read_optional_value(&name)?
+            .map(|value| self.decode_value(&name, value))
+            .transpose()
+    }
+
+    fn write_vault_values<T: Serialize>(&self, name: String, value: &T) -> Result<(), ChoError> {
+        let write = || {
+            let previous_content = self.read_configuration_content()?;
+            let mut configuration = self.parse_configuration(&previous_content)?;
+            configuration.insert(name.clone(), to_value(value)?);
+            let content = to_json_like(&configuration, Some(&previous_content))?;
+            write_atomically(&self.configuration_path(), &content)
+        };
+        write().context(|| self.encoding_context(&name))
     }
 
     fn path(&self) -> &PathBuf {
@@ -64,9 +171,13 @@ impl Vault for VaultImpl {
 pub trait VaultReadable: DeserializeOwned {
     const KEY: &'static str;
 
-    fn from_vault<V: Vault>(vault: &V) -> Result<Self, String> {
+    fn from_vault<V: Vault>(vault: &V) -> Result<Self, ChoError> {
         vault.read_vault_values(Self::KEY.into())
     }
+
+    fn from_vault_optional<V: Vault>(vault: &V) -> Result<Option<Self>, ChoError> {
+        vault.read_optional_vault_values(Self::KEY.into())
+    }
 }
 
 #[cfg(test)]
@@ -77,6 +188,7 @@ mod tests_read_vault_values {
     use serde::Deserialize;
     use tempfile::tempdir;
 
+    use crate::error::ChoError;
     use crate::vault::VaultImpl;
 
     use super::VaultReadable;
@@ -114,7 +226,7 @@ mod tests_read_vault_values {
         let vault = VaultImpl {
             path: directory.path().into(),
         };
-        let result: Result<TestVaultConfigObject, String> =
+        let result: Result<TestVaultConfigObject, ChoError> =
             TestVaultConfigObject::from_vault(&vault);
 
         assert_eq!(
@@ -125,4 +237,126 @@ mod tests_read_vault_values {
             })
         )
     }
+
+    #[test]
+    fn optional_missing_key() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(serde_json::json!({
+            "other_vault_object": {"prop_up": true}
+        }));
+
+        assert_eq!(TestVaultConfigObject::from_vault_optional(&vault), Ok(None));
+        let error = TestVaultConfigObject::from_vault(&vault).unwrap_err();
+        assert!(error.to_string().ends_with("Could not find key: vault_config_object"));
+        assert_eq!(error.exit_code(), 4);
+    }
+}
+
+#[cfg(test)]
+mod tests_write_vault_values {
+    use std::fs::{create_dir, metadata, read_to_string, rename, set_permissions, symlink_metadata, write, Permissions};
+
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::vault::{write_atomically, Vault, VaultImpl};
+
+    use super::VaultReadable;
+
+    #[derive(Deserialize, Serialize, Eq, PartialEq, Debug)]
+    struct TestVaultConfigObject {
+        prop_left: String,
+        prop_right: u16,
+    }
+
+    impl VaultReadable for TestVaultConfigObject {
+        const KEY: &'static str = "vault_config_object";
+    }
+
+    fn write_object(vault: &VaultImpl) {
+        let object = TestVaultConfigObject { prop_left: "foo".into(), prop_right: 16 };
+        vault.write_vault_values(TestVaultConfigObject::KEY.into(), &object).expect("Can write key");
+    }
+
+    fn written_keys(vault: &VaultImpl) -> Vec<String> {
+        let content = read_to_string(vault.path.join("config.json")).unwrap();
+        let configuration: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content).unwrap();
+        configuration.keys().cloned().collect()
+    }
+
+    #[test]
+    fn replace_key() {
+        let (directory, vault) = VaultImpl::create_mocked_vault(json!({
+            "zebra": {"prop_up": true},
+            "vault_config_object": {"prop_left": "bar", "prop_right": 15},
+            "antelope": [1, 2]
+        }));
+
+        let object = TestVaultConfigObject { prop_left: "foo".into(), prop_right: 16 };
+        vault.write_vault_values(TestVaultConfigObject::KEY.into(), &object).expect("Can write key");
+
+        assert_eq!(TestVaultConfigObject::from_vault(&vault), Ok(object));
+        assert_eq!(written_keys(&vault), vec!["zebra", "vault_config_object", "antelope"]);
+        // The temporary file was renamed over the configuration
+        assert_eq!(directory.path().read_dir().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn add_key() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({"zebra": {"prop_up": true}}));
+
+        write_object(&vault);
+
+        assert_eq!(written_keys(&vault), vec!["zebra", "vault_config_object"]);
+        // The mocked configuration is written on one line
+        assert_eq!(
+            read_to_string(vault.path.join("config.json")).unwrap(),
+            "{\"zebra\":{\"prop_up\":true},\"vault_config_object\":{\"prop_left\":\"foo\",\"prop_right\":16}}\n"
+        );
+    }
+
+    #[test]
+    fn keep_indentation() {
+        let (_directory, vault) = VaultImpl::create_mocked_vault(json!({}));
+        write(vault.path.join("config.json"), "{\n    \"zebra\": {\n        \"prop_up\": true\n    }\n}").unwrap();
+
+        write_object(&vault);
+
+        assert_eq!(
+            read_to_string(vault.path.join("config.json")).unwrap(),
+            "{\n    \"zebra\": {\n        \"prop_up\": true\n    },\n    \"vault_config_object\": {\n        \"prop_left\": \"foo\",\n        \"prop_right\": 16\n    }\n}\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_permissions_and_links() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let (directory, vault) = VaultImpl::create_mocked_vault(json!({"zebra": {"prop_up": true}}));
+        let real_configuration = directory.path().join("real.json");
+        rename(vault.path.join("config.json"), &real_configuration).unwrap();
+        set_permissions(&real_configuration, Permissions::from_mode(0o644)).unwrap();
+        symlink(&real_configuration, vault.path.join("config.json")).unwrap();
+
+        write_object(&vault);
+
+        assert!(symlink_metadata(vault.path.join("config.json")).unwrap().file_type().is_symlink());
+        assert_eq!(metadata(&real_configuration).unwrap().permissions().mode() & 0o777, 0o644);
+        assert_eq!(written_keys(&vault), vec!["zebra", "vault_config_object"]);
+    }
+
+    #[test]
+    fn unwritable_file() {
+        let (directory, _vault) = VaultImpl::create_mocked_vault(json!({}));
+        // Even root cannot replace a directory with a file
+        let occupied = directory.path().join("occupied.json");
+        create_dir(&occupied).unwrap();
+        let error = write_atomically(&occupied, "{}").unwrap_err();
+        assert_eq!(error.exit_code(), 3, "{}", error);
+        assert_eq!(directory.path().read_dir().unwrap().count(), 2);
+
+        let error = write_atomically(&directory.path().join("missing").join("new.json"), "{}").unwrap_err();
+        assert_eq!(error.exit_code(), 3, "{}", error);
+    }
 }